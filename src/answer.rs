@@ -0,0 +1,106 @@
+//! Answers to security questions, which should be as hard to guess as a
+//! password but are often read out over the phone.
+//!
+//! The final hash is generated like the SuperGenPass password, with the same
+//! rounds and password policy. The SHA-512 digest of the hash and the question
+//! label picks the letters of the answer, alternating consonants and vowels so
+//! that it can be pronounced. Labels are compared ignoring case and extra
+//! whitespace, so every question of a site gets its own answer, reproducible
+//! without storing it.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustgenpass::GenerateConfig;
+//! use rustgenpass::answer::generate;
+//! let generated = generate("masterpassword", "example.com", GenerateConfig::default(), "Mother's maiden name");
+//! assert_eq!("gomolekili", generated.unwrap().password);
+//! ```
+
+use crate::big_number::div_rem;
+use crate::{GenerateConfig, GeneratedPassword, final_hash};
+use sha2::{Digest, Sha512};
+use std::fmt;
+
+/// Consonants of an answer, leaving out those pronounced differently
+/// depending on the letters around them.
+const CONSONANTS: &[u8] = b"bdfghjklmnprstvz";
+
+/// Vowels of an answer.
+const VOWELS: &[u8] = b"aeiou";
+
+/// Generate the answer to a question from the final SuperGenPass hash.
+///
+/// # Arguments
+///
+/// * `password` - Master password to generate answer from
+/// * `domain` - Domain to generate answer for
+/// * `config` - Secret, rounds and hash algorithm, and the number of letters
+/// * `question` - Label of the question, such as `mother's maiden name`
+pub fn generate(
+    password: &str,
+    domain: &str,
+    config: GenerateConfig,
+    question: &str,
+) -> Result<GeneratedPassword, AnswerError> {
+    let question = normalize(question);
+    if question.is_empty() {
+        return Err(AnswerError::EmptyQuestion);
+    }
+    let (hash, rounds) = final_hash(password, domain, &config);
+    let mut entropy: [u8; 64] = Sha512::new()
+        .chain_update(hash)
+        .chain_update([0])
+        .chain_update(question)
+        .finalize()
+        .into();
+    let answer = (0..config.length)
+        .map(|i| {
+            let letters = if i % 2 == 0 { CONSONANTS } else { VOWELS };
+            char::from(letters[div_rem(&mut entropy, letters.len())])
+        })
+        .collect();
+    Ok(GeneratedPassword {
+        password: answer,
+        rounds,
+    })
+}
+
+/// Entropy in bits of the answers of `length` letters.
+///
+/// # Examples
+///
+/// ```
+/// use rustgenpass::answer::entropy;
+/// assert_eq!(31.6, (entropy(10) * 10.0).round() / 10.0);
+/// ```
+pub fn entropy(length: usize) -> f64 {
+    let consonants = length.div_ceil(2) as f64;
+    let vowels = (length / 2) as f64;
+    consonants * (CONSONANTS.len() as f64).log2() + vowels * (VOWELS.len() as f64).log2()
+}
+
+/// Lowercase label with single spaces between its words.
+fn normalize(question: &str) -> String {
+    question
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Question no answer can be generated for.
+#[derive(Debug, PartialEq)]
+pub enum AnswerError {
+    EmptyQuestion,
+}
+
+impl fmt::Display for AnswerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnswerError::EmptyQuestion => write!(f, "The question label is empty"),
+        }
+    }
+}
+
+impl std::error::Error for AnswerError {}
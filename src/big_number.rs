@@ -0,0 +1,25 @@
+//! Arithmetic on big-endian numbers of any size, for picking characters and
+//! words from a hash without modulo bias.
+
+/// Divide a big-endian number in place, returning the remainder.
+pub(crate) fn div_rem(number: &mut [u8], divisor: usize) -> usize {
+    let mut remainder = 0;
+    for byte in number.iter_mut() {
+        let value = remainder << 8 | usize::from(*byte);
+        *byte = (value / divisor) as u8;
+        remainder = value % divisor;
+    }
+    remainder
+}
+
+#[cfg(test)]
+mod test_div_rem {
+    use super::*;
+
+    #[test]
+    fn divides_in_place() {
+        let mut number = [0x01, 0x00, 0x07];
+        assert_eq!(3, div_rem(&mut number, 10));
+        assert_eq!([0x00, 0x19, 0x9a], number);
+    }
+}
@@ -0,0 +1,356 @@
+//! Agent holding the master password for other `rgp` invocations.
+//!
+//! The agent listens on a Unix domain socket in a directory only accessible
+//! by the user. Each connection carries one request and one response, both
+//! JSON objects preceded by their length as a 32 bit big-endian integer.
+//! Hostname isolation and site profiles are handled by the client, so the
+//! agent only hashes the isolated domain.
+
+use crate::generator::Generator;
+use rustgenpass::{GeneratedPassword, scheme::Params};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    env, fmt, fs,
+    io::{self, Read, Write},
+    os::unix::{
+        fs::{DirBuilderExt, MetadataExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    process,
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+    time::Duration,
+};
+
+/// Largest accepted frame, requests are much smaller.
+const MAX_FRAME_LENGTH: u32 = 64 * 1024;
+
+/// How long a connection may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "request", rename_all = "kebab-case")]
+pub enum Request {
+    /// Generate the password for an isolated domain.
+    Generate {
+        domain: String,
+        scheme: String,
+        params: Params,
+        /// Use the secret stored for the site instead of the `secret`
+        /// parameter.
+        site_secret: bool,
+    },
+    /// Fingerprint of the master password.
+    Fingerprint,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "response", rename_all = "kebab-case")]
+pub enum Response {
+    Password { password: String, rounds: u32 },
+    Fingerprint { fingerprint: String },
+    Error { message: String },
+}
+
+/// Connection to a running agent.
+#[derive(Debug)]
+pub struct Client {
+    path: PathBuf,
+}
+
+impl Client {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Generate the password for an isolated domain.
+    pub fn generate(
+        &self,
+        domain: &str,
+        scheme: &str,
+        params: &Params,
+        site_secret: bool,
+    ) -> Result<GeneratedPassword, AgentError> {
+        let request = Request::Generate {
+            domain: domain.to_string(),
+            scheme: scheme.to_string(),
+            params: params.clone(),
+            site_secret,
+        };
+        match self.request(&request)? {
+            Response::Password { password, rounds } => Ok(GeneratedPassword { password, rounds }),
+            response => Err(AgentError::unexpected(response)),
+        }
+    }
+
+    /// Fingerprint of the master password held by the agent.
+    pub fn fingerprint(&self) -> Result<String, AgentError> {
+        match self.request(&Request::Fingerprint)? {
+            Response::Fingerprint { fingerprint } => Ok(fingerprint),
+            response => Err(AgentError::unexpected(response)),
+        }
+    }
+
+    fn request(&self, request: &Request) -> Result<Response, AgentError> {
+        let io_error = |err| AgentError::Io(self.path.clone(), err);
+        let mut stream = UnixStream::connect(&self.path).map_err(io_error)?;
+        write_frame(&mut stream, request).map_err(io_error)?;
+        read_frame(&mut stream).map_err(io_error)
+    }
+}
+
+/// Default socket location, in the user's runtime directory if there is one.
+pub fn default_socket_path() -> PathBuf {
+    let directory = match env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) => PathBuf::from(runtime_dir).join("rgp"),
+        None => env::temp_dir().join(format!("rgp-{}", unsafe { libc::getuid() })),
+    };
+    directory.join("agent.sock")
+}
+
+/// Create the socket in a directory only accessible by the user, replacing
+/// the socket of an agent that is no longer running.
+pub fn bind(path: &Path) -> Result<UnixListener, AgentError> {
+    let io_error = |err| AgentError::Io(path.to_path_buf(), err);
+    let directory = path
+        .parent()
+        .filter(|directory| !directory.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(directory)
+        .map_err(io_error)?;
+    let metadata = fs::metadata(directory).map_err(io_error)?;
+    if metadata.uid() != unsafe { libc::getuid() } || metadata.mode() & 0o077 != 0 {
+        return Err(AgentError::Insecure(directory.to_path_buf()));
+    }
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(AgentError::Running(path.to_path_buf()));
+        }
+        fs::remove_file(path).map_err(io_error)?;
+    }
+    let listener = UnixListener::bind(path).map_err(io_error)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600)).map_err(io_error)?;
+    Ok(listener)
+}
+
+/// Keep the master password or a secret out of swap and core dumps, as far
+/// as the operating system allows.
+///
+/// Memory locks aren't inherited by forked processes, so this is done by the
+/// process that keeps running.
+pub fn lock_memory(secret: &str) {
+    if !secret.is_empty() && unsafe { libc::mlock(secret.as_ptr().cast(), secret.len()) } != 0 {
+        eprintln!(
+            "Couldn't lock secrets in memory: {}",
+            io::Error::last_os_error()
+        );
+    }
+    #[cfg(target_os = "linux")]
+    unsafe {
+        libc::prctl(libc::PR_SET_DUMPABLE, 0);
+    }
+}
+
+/// Serve requests until `lifetime` has passed, after which the master
+/// password is wiped and the socket removed.
+pub fn serve(
+    listener: UnixListener,
+    path: &Path,
+    generator: Generator,
+    lifetime: Option<Duration>,
+) -> io::Result<()> {
+    let generator = Arc::new(Mutex::new(Some(generator)));
+    if let Some(lifetime) = lifetime {
+        let generator = Arc::clone(&generator);
+        let path = path.to_path_buf();
+        thread::spawn(move || {
+            thread::sleep(lifetime);
+            drop(
+                generator
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .take(),
+            );
+            let _ = fs::remove_file(path);
+            process::exit(0);
+        });
+    }
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        let mut generator = generator.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(generator) = generator.as_mut() else {
+            break;
+        };
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let response = match read_frame(&mut stream) {
+            Ok(request) => handle(generator, request),
+            Err(err) => Response::Error {
+                message: format!("Invalid request: {}", err),
+            },
+        };
+        // A client going away doesn't affect the others.
+        let _ = write_frame(&mut stream, &response);
+    }
+    Ok(())
+}
+
+fn handle(generator: &mut Generator, request: Request) -> Response {
+    let result = match request {
+        Request::Generate {
+            domain,
+            scheme,
+            params,
+            site_secret,
+        } => generator
+            .generate_for_domain(&domain, &scheme, params, site_secret)
+            .map(|generated| Response::Password {
+                password: generated.password,
+                rounds: generated.rounds,
+            }),
+        Request::Fingerprint => generator
+            .fingerprint()
+            .map(|fingerprint| Response::Fingerprint { fingerprint }),
+    };
+    result.unwrap_or_else(|err| Response::Error {
+        message: err.to_string(),
+    })
+}
+
+fn write_frame<W: Write, T: Serialize>(writer: &mut W, message: &T) -> io::Result<()> {
+    let body = serde_json::to_vec(message)?;
+    let length = u32::try_from(body.len())
+        .ok()
+        .filter(|length| *length <= MAX_FRAME_LENGTH)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Message too long"))?;
+    writer.write_all(&length.to_be_bytes())?;
+    writer.write_all(&body)?;
+    writer.flush()
+}
+
+fn read_frame<R: Read, T: DeserializeOwned>(reader: &mut R) -> io::Result<T> {
+    let mut length = [0; 4];
+    reader.read_exact(&mut length)?;
+    let length = u32::from_be_bytes(length);
+    if length > MAX_FRAME_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Message too long",
+        ));
+    }
+    let mut body = vec![0; length as usize];
+    reader.read_exact(&mut body)?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+#[derive(Debug)]
+pub enum AgentError {
+    Io(PathBuf, io::Error),
+    Insecure(PathBuf),
+    Running(PathBuf),
+    Agent(String),
+    UnexpectedResponse,
+}
+
+impl AgentError {
+    fn unexpected(response: Response) -> Self {
+        match response {
+            Response::Error { message } => AgentError::Agent(message),
+            _ => AgentError::UnexpectedResponse,
+        }
+    }
+}
+
+impl fmt::Display for AgentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AgentError::Io(path, err) => {
+                write!(f, "Couldn't talk to agent at {}: {}", path.display(), err)
+            }
+            AgentError::Insecure(path) => write!(
+                f,
+                "Agent socket directory {} must only be accessible by its owner",
+                path.display()
+            ),
+            AgentError::Running(path) => {
+                write!(f, "An agent is already running at {}", path.display())
+            }
+            AgentError::Agent(message) => write!(f, "Agent: {}", message),
+            AgentError::UnexpectedResponse => write!(f, "Unexpected response from agent"),
+        }
+    }
+}
+
+impl std::error::Error for AgentError {}
+
+#[cfg(test)]
+mod test_agent {
+    use super::*;
+    use crate::sites::SiteStore;
+
+    fn generator() -> Generator {
+        Generator::with_store("masterpassword".to_string(), SiteStore::default())
+    }
+
+    #[test]
+    fn round_trips_frames() {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, &Request::Fingerprint).unwrap();
+        assert_eq!(br#"{"request":"fingerprint"}"#.len(), buffer.len() - 4);
+        let request: Request = read_frame(&mut buffer.as_slice()).unwrap();
+        assert!(matches!(request, Request::Fingerprint));
+    }
+
+    #[test]
+    fn rejects_oversized_frames() {
+        let mut buffer = (MAX_FRAME_LENGTH + 1).to_be_bytes().to_vec();
+        buffer.extend_from_slice(b"{}");
+        assert!(read_frame::<_, Request>(&mut buffer.as_slice()).is_err());
+    }
+
+    fn generate_request(scheme: &str, params: &[(&str, &str)]) -> Request {
+        Request::Generate {
+            domain: "example.com".to_string(),
+            scheme: scheme.to_string(),
+            params: params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            site_secret: false,
+        }
+    }
+
+    #[test]
+    fn generates_passwords() {
+        let request = generate_request("supergenpass", &[("length", "10"), ("rounds", "10")]);
+        assert!(matches!(
+            handle(&mut generator(), request),
+            Response::Password { password, rounds: 10 } if password == "jHMOHn7bRs"
+        ));
+
+        let request = generate_request("pwdhash", &[]);
+        assert!(matches!(
+            handle(&mut generator(), request),
+            Response::Password { password, .. } if password == "VNPpJ4Wmq2r5t2XH"
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        for request in [
+            generate_request("supergenpass", &[("length", "40")]),
+            generate_request("pwdhash", &[("length", "10")]),
+            generate_request("unknown", &[]),
+        ] {
+            assert!(matches!(
+                handle(&mut generator(), request),
+                Response::Error { .. }
+            ));
+        }
+    }
+}
@@ -0,0 +1,349 @@
+//! Generating passwords for many domains read one per line.
+//!
+//! Blank lines and lines starting with `#` are skipped. A failing line is
+//! reported in the output instead of aborting the whole batch.
+
+use crate::cli::{BatchFormat, Cli};
+use crate::generator::Generator;
+use crate::options::{self, Scope};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io::{self, BufRead, Write};
+
+/// Outcome of a single input line.
+#[derive(Debug, Serialize)]
+pub struct Record {
+    pub line: usize,
+    pub input: String,
+    pub domain: Option<String>,
+    pub password: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Generate passwords for every line of the input, returning whether all
+/// lines succeeded.
+pub fn run<R, W, F>(
+    reader: R,
+    mut writer: W,
+    format: &BatchFormat,
+    generator: &mut Generator,
+    cli: &Cli,
+    from_command_line: F,
+) -> io::Result<bool>
+where
+    R: BufRead,
+    W: Write,
+    F: Fn(&str) -> bool,
+{
+    let mut records = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        // Only the domain / URL is echoed, the overrides may hold a secret.
+        let mut record = Record {
+            line: index + 1,
+            input: line
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            domain: None,
+            password: None,
+            error: None,
+        };
+        let result = parse_line(line, cli).and_then(|(input, cli, overrides)| {
+            generator
+                .generate(&input, cli, |id| {
+                    overrides.contains(&id) || from_command_line(id)
+                })
+                .map_err(|err| err.to_string())
+        });
+        match result {
+            Ok(generated)
+                if matches!(format, BatchFormat::Tsv)
+                    && generated.password.contains(['\t', '\n', '\r']) =>
+            {
+                record.error = Some(
+                    "The password contains a tab or line break, use --batch-format csv or json"
+                        .to_string(),
+                );
+            }
+            Ok(generated) => {
+                record.domain = Some(generated.domain);
+                record.password = Some(generated.password);
+            }
+            Err(err) => record.error = Some(err),
+        }
+        records.push(record);
+    }
+    write_records(&mut writer, format, &records)?;
+    Ok(records.iter().all(|record| record.error.is_none()))
+}
+
+/// Split a line into the domain / URL and the options with its overrides
+/// applied, listing the overridden options.
+pub fn parse_line(line: &str, cli: &Cli) -> Result<(String, Cli, Vec<&'static str>), String> {
+    let mut fields = line.split_whitespace();
+    let input = fields.next().unwrap_or_default().to_string();
+    let mut cli = cli.clone();
+    let mut overrides = Vec::new();
+    for field in fields {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid override: {}", field))?;
+        overrides.push(apply_override(&mut cli, key, value)?);
+    }
+    Ok((input, cli, overrides))
+}
+
+/// Set the option named `key` from its string value, returning the id of the
+/// option.
+pub fn apply_override(cli: &mut Cli, key: &str, value: &str) -> Result<&'static str, String> {
+    let setting =
+        options::find(key, Scope::Line).ok_or_else(|| format!("Unknown override: {}", key))?;
+    (setting.set)(cli, value).ok_or_else(|| format!("Invalid value for {}: {}", key, value))?;
+    Ok(setting.id)
+}
+
+/// Apply the overrides of a JSON request, with the same keys as in batch
+/// mode, listing the overridden options.
+pub fn apply_json_overrides(
+    request: Map<String, Value>,
+    cli: &Cli,
+) -> Result<(Cli, Vec<&'static str>), String> {
+    let mut cli = cli.clone();
+    let mut overrides = Vec::new();
+    for (key, value) in request {
+        let value = match value {
+            Value::String(value) => value,
+            Value::Number(_) | Value::Bool(_) => value.to_string(),
+            _ => return Err(format!("Invalid value for {}: {}", key, value)),
+        };
+        overrides.push(apply_override(&mut cli, &key, &value)?);
+    }
+    Ok((cli, overrides))
+}
+
+fn write_records<W: Write>(
+    writer: &mut W,
+    format: &BatchFormat,
+    records: &[Record],
+) -> io::Result<()> {
+    match format {
+        BatchFormat::Json => {
+            serde_json::to_writer_pretty(&mut *writer, records)?;
+            writeln!(writer)
+        }
+        BatchFormat::Tsv | BatchFormat::Csv => {
+            let separator = if let BatchFormat::Tsv = format {
+                "\t"
+            } else {
+                ","
+            };
+            // Fields of TSV never contain tabs or line breaks, as the input
+            // is split on whitespace and such passwords are rejected.
+            let escape = |field: &str| match format {
+                BatchFormat::Csv if field.contains([',', '"', '\n', '\r']) => {
+                    format!("\"{}\"", field.replace('"', "\"\""))
+                }
+                _ => field.to_string(),
+            };
+            writeln!(
+                writer,
+                "{}",
+                ["line", "input", "domain", "password", "error"].join(separator)
+            )?;
+            for record in records {
+                let fields = [
+                    record.line.to_string(),
+                    escape(&record.input),
+                    escape(record.domain.as_deref().unwrap_or_default()),
+                    escape(record.password.as_deref().unwrap_or_default()),
+                    escape(record.error.as_deref().unwrap_or_default()),
+                ];
+                writeln!(writer, "{}", fields.join(separator))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_batch {
+    use super::*;
+    use crate::cli::Scheme;
+    use crate::sites::SiteStore;
+    use clap::Parser;
+    use rustgenpass::{
+        HashAlgorithm,
+        passphrase::Capitalization,
+        passwordmaker::{Algorithm, LeetMode},
+        scheme::Format,
+        spectre::Template,
+    };
+
+    fn cli() -> Cli {
+        Cli::parse_from(["rgp"])
+    }
+
+    #[test]
+    fn parses_overrides() {
+        let (input, cli, overrides) =
+            parse_line("https://www.example.com length=16 hash=sha512", &cli()).unwrap();
+        assert_eq!("https://www.example.com", input);
+        assert_eq!(Some(16), cli.length);
+        assert!(matches!(cli.hash, HashAlgorithm::SHA512));
+        assert_eq!(vec!["length", "hash"], overrides);
+    }
+
+    #[test]
+    fn rejects_unknown_override() {
+        assert!(parse_line("example.com salt=2", &cli()).is_err());
+        assert!(parse_line("example.com clip=true", &cli()).is_err());
+    }
+
+    #[test]
+    fn parses_scheme_overrides() {
+        let (_, cli, overrides) =
+            parse_line("example.com scheme=spectre counter=2 template=pin", &cli()).unwrap();
+        assert_eq!(Scheme::Spectre, cli.scheme);
+        assert_eq!(2, cli.counter);
+        assert!(matches!(cli.template, Template::Pin));
+        assert_eq!(vec!["scheme", "counter", "template"], overrides);
+    }
+
+    #[test]
+    fn parses_lesspass_overrides() {
+        let (_, cli, overrides) = parse_line(
+            "example.org scheme=lesspass login=contact@example.org length=32 no-symbols=true",
+            &cli(),
+        )
+        .unwrap();
+        assert_eq!(Scheme::Lesspass, cli.scheme);
+        assert_eq!(Some("contact@example.org".to_string()), cli.login);
+        assert_eq!(Some(32), cli.length);
+        assert!(cli.no_symbols);
+        assert_eq!(vec!["scheme", "login", "length", "no_symbols"], overrides);
+    }
+
+    #[test]
+    fn parses_passwordmaker_overrides() {
+        let (_, cli, overrides) = parse_line(
+            "example.com scheme=passwordmaker algorithm=hmac-sha256 leet=both leet-level=3",
+            &cli(),
+        )
+        .unwrap();
+        assert_eq!(Scheme::Passwordmaker, cli.scheme);
+        assert_eq!(Algorithm::HmacSha256, cli.algorithm);
+        assert_eq!(LeetMode::Both, cli.leet);
+        assert_eq!(3, cli.leet_level);
+        assert_eq!(vec!["scheme", "algorithm", "leet", "leet_level"], overrides);
+        assert!(parse_line("example.com leet-level=10", &cli).is_err());
+    }
+
+    #[test]
+    fn parses_passphrase_overrides() {
+        let (_, cli, overrides) = parse_line(
+            "example.com format=passphrase words=4 separator=. capitalize=title digit=true",
+            &cli(),
+        )
+        .unwrap();
+        assert_eq!(Format::Passphrase, cli.format);
+        assert_eq!(4, cli.words);
+        assert_eq!(".", cli.separator);
+        assert_eq!(Capitalization::Title, cli.capitalize);
+        assert!(cli.digit);
+        assert_eq!(
+            vec!["format", "words", "separator", "capitalize", "digit"],
+            overrides
+        );
+        assert!(parse_line("example.com words=2", &cli).is_err());
+    }
+
+    #[test]
+    fn parses_pin_overrides() {
+        let (_, cli, overrides) = parse_line(
+            "example.com format=pin pin-length=6 reject-weak=true",
+            &cli(),
+        )
+        .unwrap();
+        assert_eq!(Format::Pin, cli.format);
+        assert_eq!(6, cli.pin_length);
+        assert!(cli.reject_weak);
+        assert_eq!(vec!["format", "pin_length", "reject_weak"], overrides);
+        assert!(parse_line("example.com pin-length=10", &cli).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_length() {
+        assert!(parse_line("example.com length=40", &cli()).is_err());
+    }
+
+    fn run_batch(input: &str, format: BatchFormat, cli: &Cli) -> String {
+        let mut generator =
+            Generator::with_store("masterpassword".to_string(), SiteStore::default());
+        let mut output = Vec::new();
+        run(
+            input.as_bytes(),
+            &mut output,
+            &format,
+            &mut generator,
+            cli,
+            |_| false,
+        )
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn echoes_only_domain_of_line() {
+        let output = run_batch(
+            "example.com secret=hunter2 length=16\n",
+            BatchFormat::Tsv,
+            &cli(),
+        );
+        assert!(output.contains("\n1\texample.com\texample.com\t"));
+        assert!(!output.contains("hunter2"));
+    }
+
+    #[test]
+    fn rejects_tabs_in_tsv() {
+        let mut cli = cli();
+        cli.format = Format::Passphrase;
+        cli.separator = "\t".to_string();
+        let output = run_batch("example.com\n", BatchFormat::Tsv, &cli);
+        assert_eq!(2, output.lines().count());
+        assert!(output.contains("tab or line break"));
+        let output = run_batch("example.com\n", BatchFormat::Csv, &cli);
+        assert!(output.contains("\t"));
+    }
+
+    #[test]
+    fn quotes_csv_fields() {
+        let records = [
+            Record {
+                line: 1,
+                input: "example.com foo".to_string(),
+                domain: None,
+                password: None,
+                error: Some("Invalid override: foo, \"bar\"".to_string()),
+            },
+            Record {
+                line: 2,
+                input: "example.org".to_string(),
+                domain: Some("example.org".to_string()),
+                password: Some("ab\rcd".to_string()),
+                error: None,
+            },
+        ];
+        let mut output = Vec::new();
+        write_records(&mut output, &BatchFormat::Csv, &records).unwrap();
+        assert_eq!(
+            "line,input,domain,password,error\n1,example.com foo,,,\"Invalid override: foo, \"\"bar\"\"\"\n2,example.org,example.org,\"ab\rcd\",\n",
+            String::from_utf8(output).unwrap()
+        );
+    }
+}
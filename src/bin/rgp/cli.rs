@@ -0,0 +1,634 @@
+//! Command line interface of the `rgp` binary.
+
+use crate::clipboard::ClipBackend;
+use crate::native_host::Browser;
+use crate::sites::site_candidates;
+use clap::{Parser, Subcommand, ValueEnum, builder::BoolishValueParser};
+use clap_complete::ArgValueCandidates;
+use rustgenpass::{
+    GenerateConfig, HashAlgorithm, HostnameConfig,
+    passphrase::Capitalization,
+    passwordmaker::{Algorithm, LeetMode},
+    scheme::Format,
+    spectre::Template,
+};
+use std::{fmt, path::PathBuf};
+
+#[derive(Parser, Clone, Debug)]
+#[clap(author, version, about, long_about = None)]
+/// Options parsed from command line
+///
+/// Every option except the master password can also be set through an
+/// `RGP_*` environment variable or the configuration file. The options are
+/// global, so they can be given both before and after a subcommand.
+pub struct Cli {
+    /// Master password, if neither given nor read with a --password-* option,
+    /// it's taken from the agent at RGP_AGENT_SOCK or prompted for
+    #[clap(short, long, global = true, value_parser, group = "password_input")]
+    pub password: Option<String>,
+
+    /// Read master password from the first line of stdin
+    #[clap(long, global = true, action, group = "password_input")]
+    pub password_stdin: bool,
+
+    /// Read master password from a file that isn't world-readable
+    #[clap(
+        long,
+        global = true,
+        value_parser,
+        value_name = "PATH",
+        group = "password_input"
+    )]
+    pub password_file: Option<PathBuf>,
+
+    /// Read master password from an open file descriptor
+    #[clap(
+        long,
+        global = true,
+        value_parser,
+        value_name = "FD",
+        group = "password_input"
+    )]
+    pub password_fd: Option<u32>,
+
+    /// Read master password from an environment variable
+    #[clap(
+        long,
+        global = true,
+        value_parser,
+        value_name = "VAR",
+        group = "password_input"
+    )]
+    pub password_env: Option<String>,
+
+    /// Secret added to the master password
+    #[clap(
+        short,
+        long,
+        global = true,
+        value_parser,
+        env = "RGP_SECRET",
+        hide_env_values = true
+    )]
+    pub secret: Option<String>,
+
+    /// Domain / URL to generate password for
+    #[clap(
+        short,
+        long,
+        global = true,
+        value_parser,
+        env = "RGP_DOMAIN",
+        add = ArgValueCandidates::new(site_candidates)
+    )]
+    pub domain: Option<String>,
+
+    /// Length of generated password, min: 4, max: 24, or 35 with the lesspass
+    /// scheme [default: 10, 16 for lesspass and 8 for passwordmaker]
+    #[clap(short, long, global = true, value_parser = clap::value_parser!(u8).range(4..=35), env = "RGP_LENGTH")]
+    pub length: Option<u8>,
+
+    /// Number of hash rounds
+    #[clap(short, long, global = true, default_value_t = 10, env = "RGP_ROUNDS")]
+    pub rounds: u8,
+
+    /// Don't remove subdomains from domain
+    #[clap(short, long, global = true, action, value_parser = BoolishValueParser::new(), env = "RGP_KEEP_SUBDOMAINS")]
+    pub keep_subdomains: bool,
+
+    /// Passthrough domain unmodified to hash function
+    #[clap(short = 'P', long, global = true, action, value_parser = BoolishValueParser::new(), env = "RGP_PASSTHROUGH")]
+    pub passthrough: bool,
+
+    /// Hashing method to use
+    #[clap(short = 'H', long, global = true, value_enum, default_value_t = HashAlgorithm::MD5, env = "RGP_HASH")]
+    pub hash: HashAlgorithm,
+
+    /// Scheme generating the password
+    #[clap(long, global = true, value_enum, default_value_t = Scheme::Supergenpass, env = "RGP_SCHEME")]
+    pub scheme: Scheme,
+
+    /// Full name of the user, for the spectre scheme
+    #[clap(long, global = true, value_parser, env = "RGP_FULL_NAME")]
+    pub full_name: Option<String>,
+
+    /// Counter of the site password, for the spectre and lesspass schemes
+    #[clap(long, global = true, default_value_t = 1, env = "RGP_COUNTER")]
+    pub counter: u32,
+
+    /// Template class of the site password, for the spectre scheme
+    #[clap(long, global = true, value_enum, default_value_t = Template::Long, env = "RGP_TEMPLATE")]
+    pub template: Template,
+
+    /// Login at the site, for the lesspass and passwordmaker schemes
+    #[clap(long, global = true, value_parser, env = "RGP_LOGIN")]
+    pub login: Option<String>,
+
+    /// Leave out lowercase letters, for the lesspass scheme
+    #[clap(long, global = true, action, value_parser = BoolishValueParser::new(), env = "RGP_NO_LOWERCASE")]
+    pub no_lowercase: bool,
+
+    /// Leave out uppercase letters, for the lesspass scheme
+    #[clap(long, global = true, action, value_parser = BoolishValueParser::new(), env = "RGP_NO_UPPERCASE")]
+    pub no_uppercase: bool,
+
+    /// Leave out digits, for the lesspass scheme
+    #[clap(long, global = true, action, value_parser = BoolishValueParser::new(), env = "RGP_NO_DIGITS")]
+    pub no_digits: bool,
+
+    /// Leave out symbols, for the lesspass scheme
+    #[clap(long, global = true, action, value_parser = BoolishValueParser::new(), env = "RGP_NO_SYMBOLS")]
+    pub no_symbols: bool,
+
+    /// Hash algorithm, for the passwordmaker scheme
+    #[clap(long, global = true, value_enum, default_value_t = Algorithm::Md5, env = "RGP_ALGORITHM")]
+    pub algorithm: Algorithm,
+
+    /// Characters of the password, for the supergenpass and passwordmaker
+    /// schemes [default: base64 for supergenpass, letters, digits and symbols
+    /// for passwordmaker]
+    #[clap(long, global = true, value_parser, env = "RGP_CHARSET")]
+    pub charset: Option<String>,
+
+    /// Modifier added to the text to use, for the passwordmaker scheme
+    #[clap(long, global = true, value_parser, env = "RGP_MODIFIER")]
+    pub modifier: Option<String>,
+
+    /// Prefix of the password, for the passwordmaker scheme
+    #[clap(long, global = true, value_parser, env = "RGP_PREFIX")]
+    pub prefix: Option<String>,
+
+    /// Suffix of the password, for the passwordmaker scheme
+    #[clap(long, global = true, value_parser, env = "RGP_SUFFIX")]
+    pub suffix: Option<String>,
+
+    /// When to apply l33t, for the passwordmaker scheme
+    #[clap(long, global = true, value_enum, default_value_t = LeetMode::Off, env = "RGP_LEET")]
+    pub leet: LeetMode,
+
+    /// Level of l33t, for the passwordmaker scheme, min: 1, max: 9
+    #[clap(long, global = true, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=9), env = "RGP_LEET_LEVEL")]
+    pub leet_level: u8,
+
+    /// Format of the password, for the supergenpass scheme
+    #[clap(long, global = true, value_enum, default_value_t = Format::Password, env = "RGP_FORMAT")]
+    pub format: Format,
+
+    /// Number of words, for the passphrase format, min: 3, max: 20
+    #[clap(long, global = true, default_value_t = 6, value_parser = clap::value_parser!(u8).range(3..=20), env = "RGP_WORDS")]
+    pub words: u8,
+
+    /// Separator between words, for the passphrase format
+    #[clap(long, global = true, default_value = "-", env = "RGP_SEPARATOR")]
+    pub separator: String,
+
+    /// Capitalization of words, for the passphrase format
+    #[clap(long, global = true, value_enum, default_value_t = Capitalization::Lower, env = "RGP_CAPITALIZE")]
+    pub capitalize: Capitalization,
+
+    /// Add a digit to one of the words, for the passphrase format
+    #[clap(long, global = true, action, value_parser = BoolishValueParser::new(), env = "RGP_DIGIT")]
+    pub digit: bool,
+
+    /// Number of digits, for the pin format, min: 4, max: 8
+    #[clap(long, global = true, default_value_t = 4, value_parser = clap::value_parser!(u8).range(4..=8), env = "RGP_PIN_LENGTH")]
+    pub pin_length: u8,
+
+    /// Reject repeated, sequential and date-like PINs, for the pin format
+    #[clap(long, global = true, action, value_parser = BoolishValueParser::new(), env = "RGP_REJECT_WEAK")]
+    pub reject_weak: bool,
+
+    /// Output format of the generated password
+    #[clap(short, long, global = true, value_enum, default_value_t = OutputFormat::Plain, env = "RGP_OUTPUT")]
+    pub output: OutputFormat,
+
+    /// Show the password as a QR code instead of printing it
+    #[clap(long, global = true, action, value_parser = BoolishValueParser::new(), env = "RGP_QR")]
+    pub qr: bool,
+
+    /// Write a QR code of the password to a PNG or SVG file instead of
+    /// printing it
+    #[clap(
+        long,
+        global = true,
+        value_parser,
+        value_name = "PATH",
+        env = "RGP_QR_FILE"
+    )]
+    pub qr_file: Option<PathBuf>,
+
+    /// Explain the strength of the password and the master password on
+    /// stderr
+    #[clap(long, global = true, action, value_parser = BoolishValueParser::new(), env = "RGP_EXPLAIN")]
+    pub explain: bool,
+
+    /// Socket of a running `rgp agent` to use instead of the master password
+    #[clap(
+        long,
+        global = true,
+        value_parser,
+        value_name = "PATH",
+        env = "RGP_AGENT_SOCK"
+    )]
+    pub agent_sock: Option<PathBuf>,
+
+    /// Copy the password to the clipboard instead of printing it
+    #[clap(long, global = true, action, value_parser = BoolishValueParser::new(), env = "RGP_CLIP")]
+    pub clip: bool,
+
+    /// Clipboard tools used to copy and paste, detected if not given
+    #[clap(long, global = true, value_enum, env = "RGP_CLIP_BACKEND")]
+    pub clip_backend: Option<ClipBackend>,
+
+    /// Seconds after which the copied password is cleared, 0 to keep it
+    #[clap(
+        long,
+        global = true,
+        default_value_t = 45,
+        value_name = "SECONDS",
+        env = "RGP_CLIP_TIMEOUT"
+    )]
+    pub clip_timeout: u64,
+
+    /// Configuration file to read defaults from
+    #[clap(short, long, global = true, value_parser, env = "RGP_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+/// Subcommands of the binary, `generate` is used when none is given
+pub enum Command {
+    /// Generate a password, the default when no subcommand is given
+    Generate,
+
+    /// Generate the answer to a security question of the site
+    ///
+    /// The answer is pronounceable and derived from the master password, the
+    /// domain and the question label, so it's reproducible without storing
+    /// it. Only the supergenpass scheme generates answers.
+    Answer {
+        /// Label of the question, e.g. "mother's maiden name"
+        #[clap(long, value_parser)]
+        question: String,
+    },
+
+    /// Print the domain a password would be generated for
+    Hostname {
+        /// Domain / URL to isolate the domain of, instead of --domain
+        url: Option<String>,
+    },
+
+    /// Check that a password is generated with the given options
+    ///
+    /// With --search, find the rounds, hashing method, subdomain handling and
+    /// use of the secret that reproduce the password instead.
+    Verify {
+        /// Password to check, prompted for if not given
+        #[clap(value_name = "PASSWORD")]
+        expected: Option<String>,
+
+        /// Search for the options that generate the password
+        #[clap(long, action)]
+        search: bool,
+
+        /// Highest number of hash rounds to search
+        #[clap(long, default_value_t = 100, requires = "search")]
+        max_rounds: u8,
+    },
+
+    /// Print the fingerprint of the master password
+    Fingerprint,
+
+    /// Generate passwords interactively, entering the master password once
+    ///
+    /// Reads domains / URLs line by line. Settings can be changed with
+    /// commands like `:length 16`, see `:help`.
+    Shell {
+        /// Seconds without input after which the master password is wiped
+        /// and the session ends, 0 to never time out
+        #[clap(long, default_value_t = 300, value_name = "SECONDS")]
+        idle_timeout: u64,
+    },
+
+    /// Hold the master password in the background for other invocations
+    ///
+    /// Prints shell commands setting RGP_AGENT_SOCK, which makes `rgp` get
+    /// passwords from the agent instead of asking for the master password,
+    /// e.g. `eval "$(rgp agent)"`.
+    #[cfg(unix)]
+    Agent {
+        /// Socket to listen on, in the user's runtime directory by default
+        #[clap(long, value_parser, value_name = "PATH")]
+        socket: Option<PathBuf>,
+
+        /// Seconds after which the master password is wiped and the agent
+        /// exits, 0 to run until killed
+        #[clap(long, default_value_t = 3600, value_name = "SECONDS")]
+        lifetime: u64,
+
+        /// Don't fork into the background
+        #[clap(long, action)]
+        foreground: bool,
+    },
+
+    /// Serve a browser extension over the native messaging protocol
+    ///
+    /// Started by the browser, through a script running `rgp native-host`
+    /// as browsers can't pass arguments to native messaging hosts.
+    #[clap(args_conflicts_with_subcommands = true)]
+    NativeHost {
+        /// Arguments passed by the browser, which are ignored
+        #[clap(hide = true, allow_hyphen_values = true)]
+        caller: Vec<String>,
+
+        #[clap(subcommand)]
+        command: Option<NativeHostCommand>,
+    },
+
+    /// Serve a JSON API over HTTP on localhost
+    ///
+    /// Requests must carry the token in an `Authorization: Bearer` header.
+    /// Without a token from the options, a random one is printed on start.
+    Serve {
+        /// Port to listen on at 127.0.0.1, 0 to pick a free one
+        #[clap(long, default_value_t = 8787)]
+        port: u16,
+
+        /// Listen on a Unix domain socket instead
+        #[cfg(unix)]
+        #[clap(long, value_parser, value_name = "PATH", conflicts_with = "port")]
+        socket: Option<PathBuf>,
+
+        /// Token required from clients
+        #[clap(long, env = "RGP_SERVE_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+
+        /// Read the token required from clients from a file
+        #[clap(long, value_parser, value_name = "PATH")]
+        token_file: Option<PathBuf>,
+    },
+
+    /// Print a script registering completions for a shell
+    ///
+    /// Completions are generated by `rgp` itself, which also completes
+    /// domains from the site store.
+    Completions {
+        /// Shell to generate the completion script for
+        #[clap(value_enum)]
+        shell: clap_complete::Shell,
+    },
+
+    /// Print a man page in roff format
+    Man,
+
+    /// Inspect the configuration
+    #[clap(subcommand)]
+    Config(ConfigCommand),
+
+    /// Manage stored site profiles
+    #[clap(subcommand)]
+    Site(SiteCommand),
+
+    /// Generate passwords for domains / URLs read one per line
+    ///
+    /// Each line may be followed by whitespace separated overrides for the
+    /// options, e.g. `example.com length=16 hash=sha512`.
+    Batch {
+        /// File to read from, reads stdin if not given or `-`
+        file: Option<PathBuf>,
+
+        /// Output format of the records
+        #[clap(short = 'f', long, value_enum, default_value_t = BatchFormat::Tsv)]
+        batch_format: BatchFormat,
+    },
+}
+
+#[derive(Subcommand, Clone, Debug)]
+/// Subcommands of `rgp config`
+pub enum ConfigCommand {
+    /// Print the effective settings and where each came from
+    Show,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+/// Subcommands of `rgp native-host`
+pub enum NativeHostCommand {
+    /// Print the manifest registering the native messaging host
+    Manifest {
+        /// Browser to register with
+        #[clap(short, long, value_enum, default_value_t = Browser::Firefox)]
+        browser: Browser,
+
+        /// Absolute path of the script running `rgp native-host`
+        #[clap(long, value_parser)]
+        path: PathBuf,
+
+        /// Extension allowed to use the host, may be repeated
+        #[clap(long = "extension-id", value_name = "ID", required = true)]
+        extension_ids: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Clone, Debug)]
+/// Subcommands of `rgp site`
+pub enum SiteCommand {
+    /// List stored sites
+    List,
+
+    /// Store the length, rounds, hash, scheme, counter, template, login,
+    /// character sets, PasswordMaker options and secret given on the command
+    /// line for a site, overriding configured defaults
+    Set {
+        /// Domain as isolated from URLs
+        #[clap(add = ArgValueCandidates::new(site_candidates))]
+        domain: String,
+
+        /// Prompt for a secret to store encrypted with the master password
+        #[clap(long, action, conflicts_with = "secret")]
+        prompt_secret: bool,
+    },
+
+    /// Remove a stored site
+    Remove {
+        /// Domain as isolated from URLs
+        #[clap(add = ArgValueCandidates::new(site_candidates))]
+        domain: String,
+    },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, ValueEnum)]
+/// Schemes generating the password
+pub enum Scheme {
+    /// SuperGenPass
+    #[default]
+    Supergenpass,
+    /// PwdHash, as long as the master password with the `@@` prefix, without
+    /// using the length, rounds, hash and secret
+    Pwdhash,
+    /// Spectre, formerly Master Password, from the full name, counter and
+    /// template, without using the length, rounds, hash and secret
+    Spectre,
+    /// LessPass, from the login, counter, length and character sets, without
+    /// using the rounds, hash and secret
+    Lesspass,
+    /// PasswordMaker, from the algorithm, login, modifier, character set,
+    /// prefix, suffix, l33t and length, without using the rounds, hash and
+    /// secret
+    Passwordmaker,
+}
+
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Scheme::Supergenpass => write!(f, "supergenpass"),
+            Scheme::Pwdhash => write!(f, "pwdhash"),
+            Scheme::Spectre => write!(f, "spectre"),
+            Scheme::Lesspass => write!(f, "lesspass"),
+            Scheme::Passwordmaker => write!(f, "passwordmaker"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+/// Output formats of the generated password
+pub enum OutputFormat {
+    /// Only the password
+    Plain,
+    /// JSON object with the password and the parameters used
+    Json,
+    /// Shell variable assignments for use with `eval`
+    Env,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+/// Output formats of `rgp batch`
+pub enum BatchFormat {
+    Tsv,
+    Csv,
+    Json,
+}
+
+impl From<Cli> for GenerateConfig {
+    fn from(cli: Cli) -> Self {
+        Self {
+            secret: cli.secret,
+            length: cli
+                .length
+                .map_or(GenerateConfig::default().length, usize::from),
+            hash_rounds: cli.rounds,
+            hash_algorithm: cli.hash,
+        }
+    }
+}
+
+impl From<Cli> for HostnameConfig {
+    fn from(cli: Cli) -> Self {
+        Self {
+            passthrough: cli.passthrough,
+            keep_subdomains: cli.keep_subdomains,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_cli {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn verifies_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn accepts_flags_without_subcommand() {
+        let cli = Cli::parse_from(["rgp", "-d", "example.com", "-l", "16"]);
+        assert!(cli.command.is_none());
+        assert_eq!(Some(16), cli.length);
+    }
+
+    #[test]
+    fn accepts_flags_after_subcommand() {
+        let cli = Cli::parse_from(["rgp", "generate", "-d", "example.com", "-l", "16"]);
+        assert!(matches!(cli.command, Some(Command::Generate)));
+        assert_eq!(Some("example.com".to_string()), cli.domain);
+        assert_eq!(Some(16), cli.length);
+    }
+
+    #[test]
+    fn accepts_format_after_subcommand() {
+        let cli = Cli::parse_from(["rgp", "generate", "--format", "passphrase"]);
+        assert_eq!(Format::Passphrase, cli.format);
+        let cli = Cli::parse_from(["rgp", "batch", "--format", "pin", "--batch-format", "csv"]);
+        assert_eq!(Format::Pin, cli.format);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Batch {
+                batch_format: BatchFormat::Csv,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn defaults_to_four_digit_pins() {
+        let cli = Cli::parse_from(["rgp", "--format", "pin"]);
+        assert_eq!(Format::Pin, cli.format);
+        assert_eq!(4, cli.pin_length);
+        assert_eq!(None, cli.length);
+    }
+
+    #[test]
+    fn accepts_question_of_answer() {
+        let cli = Cli::parse_from(["rgp", "answer", "--question", "first pet", "-l", "12"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Answer { question }) if question == "first pet"
+        ));
+        assert_eq!(Some(12), cli.length);
+    }
+
+    #[test]
+    fn ignores_browser_arguments_of_native_host() {
+        for args in [
+            vec!["chrome-extension://abcdef/", "--parent-window=0"],
+            vec![
+                "/usr/lib/mozilla/native-messaging-hosts/rustgenpass.json",
+                "rgp@example.com",
+            ],
+        ] {
+            let cli = Cli::parse_from([vec!["rgp", "native-host"], args].concat());
+            assert!(matches!(
+                cli.command,
+                Some(Command::NativeHost { command: None, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn registers_every_scheme() {
+        let registry = rustgenpass::scheme::Registry::default();
+        let command = Cli::command();
+        // Options of subcommands, like the question of `rgp answer`, count
+        // too.
+        let arguments: Vec<&str> = command
+            .get_arguments()
+            .chain(command.get_subcommands().flat_map(|c| c.get_arguments()))
+            .map(|arg| arg.get_id().as_str())
+            .collect();
+        for scheme in Scheme::value_variants() {
+            let cli = Cli::parse_from(["rgp", "--scheme", &scheme.to_string()]);
+            let registered = registry.get(&cli.scheme.to_string()).unwrap();
+            for key in registered.params() {
+                assert!(
+                    arguments.contains(&key.replace('-', "_").as_str()),
+                    "{}",
+                    key
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Copying the generated password to the clipboard instead of printing it.
+//!
+//! The clipboard is accessed through external commands, and cleared after a
+//! timeout unless something else has been copied in the meantime.
+
+use clap::ValueEnum;
+use std::{
+    env,
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+/// Clipboard the password can be copied to.
+pub trait Clipboard {
+    /// Replace the contents of the clipboard.
+    fn copy(&self, text: &str) -> io::Result<()>;
+
+    /// Current contents of the clipboard.
+    fn paste(&self) -> io::Result<String>;
+
+    /// Empty the clipboard.
+    fn clear(&self) -> io::Result<()> {
+        self.copy("")
+    }
+}
+
+/// Clear the clipboard if it still holds `text`, returning whether it did.
+pub fn clear_if_unchanged(clipboard: &dyn Clipboard, text: &str) -> io::Result<bool> {
+    let contents = clipboard.paste()?;
+    // Some paste commands add a line ending.
+    if contents.strip_suffix('\n').unwrap_or(&contents) != text {
+        return Ok(false);
+    }
+    clipboard.clear()?;
+    Ok(true)
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+/// Supported clipboard commands
+pub enum ClipBackend {
+    /// wl-copy and wl-paste on Wayland
+    WlCopy,
+    /// xclip on X11
+    Xclip,
+    /// xsel on X11
+    Xsel,
+    /// pbcopy and pbpaste on macOS
+    Pbcopy,
+}
+
+impl ClipBackend {
+    /// Pick the backend for the running system, preferring commands that are
+    /// installed.
+    pub fn detect() -> Option<Self> {
+        if cfg!(target_os = "macos") {
+            return Some(ClipBackend::Pbcopy);
+        }
+        let candidates: &[Self] = if env::var_os("WAYLAND_DISPLAY").is_some() {
+            &[ClipBackend::WlCopy, ClipBackend::Xclip, ClipBackend::Xsel]
+        } else {
+            &[ClipBackend::Xclip, ClipBackend::Xsel]
+        };
+        candidates
+            .iter()
+            .find(|backend| is_installed(&backend.commands().0[0]))
+            .cloned()
+    }
+
+    /// Copy and paste commands with their arguments.
+    fn commands(&self) -> (Vec<String>, Vec<String>) {
+        let (copy, paste): (&[&str], &[&str]) = match self {
+            ClipBackend::WlCopy => (&["wl-copy"], &["wl-paste", "--no-newline"]),
+            ClipBackend::Xclip => (
+                &["xclip", "-selection", "clipboard"],
+                &["xclip", "-selection", "clipboard", "-o"],
+            ),
+            ClipBackend::Xsel => (
+                &["xsel", "--clipboard", "--input"],
+                &["xsel", "--clipboard", "--output"],
+            ),
+            ClipBackend::Pbcopy => (&["pbcopy"], &["pbpaste"]),
+        };
+        let owned = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect();
+        (owned(copy), owned(paste))
+    }
+}
+
+/// Clipboard accessed by running a command for copying and one for pasting.
+#[derive(Debug)]
+pub struct CommandClipboard {
+    copy: Vec<String>,
+    paste: Vec<String>,
+}
+
+impl CommandClipboard {
+    /// Clipboard using arbitrary commands, the copy command reads the text
+    /// from stdin and the paste command writes it to stdout.
+    pub fn new(copy: Vec<String>, paste: Vec<String>) -> Self {
+        Self { copy, paste }
+    }
+}
+
+impl From<&ClipBackend> for CommandClipboard {
+    fn from(backend: &ClipBackend) -> Self {
+        let (copy, paste) = backend.commands();
+        Self::new(copy, paste)
+    }
+}
+
+impl Clipboard for CommandClipboard {
+    fn copy(&self, text: &str) -> io::Result<()> {
+        // Output is discarded, as some commands keep running in the
+        // background to serve the clipboard.
+        let mut child = command(&self.copy)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        check_status(&self.copy, child.wait()?)
+    }
+
+    fn paste(&self) -> io::Result<String> {
+        let output = command(&self.paste).stderr(Stdio::null()).output()?;
+        check_status(&self.paste, output.status)?;
+        String::from_utf8(output.stdout)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+fn command(args: &[String]) -> Command {
+    let mut command = Command::new(&args[0]);
+    command.args(&args[1..]);
+    command
+}
+
+fn check_status(args: &[String], status: std::process::ExitStatus) -> io::Result<()> {
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{} failed: {}", args[0], status)))
+    }
+}
+
+fn is_installed(program: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|paths| env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+#[cfg(all(test, unix))]
+mod test_clipboard {
+    use super::*;
+    use std::{fs, path::PathBuf};
+
+    /// Clipboard backed by a file through `sh`.
+    fn fake_clipboard(name: &str) -> (CommandClipboard, PathBuf) {
+        let path = env::temp_dir().join(format!("rgp-clip-{}-{}", name, std::process::id()));
+        let file = path.to_str().unwrap();
+        let sh = |script: String| vec!["sh".to_string(), "-c".to_string(), script];
+        let clipboard = CommandClipboard::new(
+            sh(format!("cat > '{}'", file)),
+            sh(format!("cat '{}'", file)),
+        );
+        (clipboard, path)
+    }
+
+    #[test]
+    fn copies_and_pastes() {
+        let (clipboard, path) = fake_clipboard("copy");
+        clipboard.copy("jHMOHn7bRs").unwrap();
+        assert_eq!("jHMOHn7bRs", clipboard.paste().unwrap());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn clears_unchanged_clipboard() {
+        let (clipboard, path) = fake_clipboard("unchanged");
+        clipboard.copy("jHMOHn7bRs").unwrap();
+        assert!(clear_if_unchanged(&clipboard, "jHMOHn7bRs").unwrap());
+        assert_eq!("", clipboard.paste().unwrap());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn keeps_changed_clipboard() {
+        let (clipboard, path) = fake_clipboard("changed");
+        clipboard.copy("jHMOHn7bRs").unwrap();
+        clipboard.copy("something else").unwrap();
+        assert!(!clear_if_unchanged(&clipboard, "jHMOHn7bRs").unwrap());
+        assert_eq!("something else", clipboard.paste().unwrap());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn reports_failing_command() {
+        let clipboard = CommandClipboard::new(vec!["false".to_string()], vec!["false".to_string()]);
+        assert!(clipboard.copy("jHMOHn7bRs").is_err());
+        assert!(clipboard.paste().is_err());
+    }
+}
@@ -0,0 +1,27 @@
+//! Running in the background, detached from the terminal.
+
+use std::{fs, io, os::unix::io::AsRawFd};
+
+/// Fork into the background, returning the process id of the child in the
+/// parent and `None` in the child itself.
+pub fn daemonize() -> io::Result<Option<i32>> {
+    match unsafe { libc::fork() } {
+        -1 => Err(io::Error::last_os_error()),
+        0 => {
+            // Detach from the terminal and from the output of the caller,
+            // which may be waiting for it to close.
+            unsafe { libc::setsid() };
+            let null = fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open("/dev/null")?;
+            for fd in 0..=2 {
+                if unsafe { libc::dup2(null.as_raw_fd(), fd) } == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(None)
+        }
+        pid => Ok(Some(pid)),
+    }
+}
@@ -0,0 +1,304 @@
+//! Explanation of how strong a generated password is, for `--explain`.
+//!
+//! The master password is checked with zxcvbn. Only its estimated strength
+//! and generic advice are reported, never the password or parts of it.
+
+use crate::generator::{Generated, Rendering};
+use clap::ValueEnum;
+use rustgenpass::{GenerateConfig, HashAlgorithm, answer, charset, entropy, passphrase, pin};
+use std::{collections::BTreeSet, fmt::Write};
+use zxcvbn::zxcvbn;
+
+/// Lowest zxcvbn score of a master password not warned about.
+const MIN_SCORE: u8 = 3;
+
+/// Estimated strength of a master password.
+pub struct MasterStrength {
+    score: u8,
+    bits: f64,
+    crack_time: String,
+    warning: Option<String>,
+    suggestions: Vec<String>,
+}
+
+/// Estimate the strength of a master password, penalizing the words in
+/// `user_inputs`, such as the domain.
+pub fn master_strength(password: &str, user_inputs: &[&str]) -> MasterStrength {
+    let estimate = zxcvbn(password, user_inputs);
+    let feedback = estimate.feedback();
+    MasterStrength {
+        score: estimate.score().into(),
+        bits: estimate.guesses_log10() * 10_f64.log2(),
+        // Anyone knowing a single generated password can test master
+        // passwords offline, at the speed of a fast hash.
+        crack_time: estimate
+            .crack_times()
+            .offline_fast_hashing_1e10_per_second()
+            .to_string(),
+        warning: feedback
+            .and_then(|feedback| feedback.warning())
+            .map(|warning| warning.to_string()),
+        suggestions: feedback
+            .map(|feedback| {
+                feedback
+                    .suggestions()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// Explain the strength of a generated password, and of the master password
+/// if it's known.
+pub fn explain(generated: &Generated, master: Option<&MasterStrength>) -> String {
+    let mut explanation = String::new();
+    // Entropy in bits, if estimated, and a note on how it was estimated.
+    let estimate = match &generated.rendering {
+        Rendering::Base64 => {
+            let entropy = entropy(&GenerateConfig {
+                length: generated.length,
+                hash_algorithm: HashAlgorithm::from_str(&generated.algorithm, true)
+                    .unwrap_or_default(),
+                ..GenerateConfig::default()
+            });
+            let _ = writeln!(
+                explanation,
+                "Password for {}: {} characters of a base64 encoded {} hash, with + and / replaced by 9 and 8",
+                generated.domain, generated.length, generated.algorithm
+            );
+            let _ = writeln!(
+                explanation,
+                "  Password policy: {:.0}% of hashes accepted, {:.1} extra rounds on average, {} rounds this time",
+                entropy.acceptance_rate * 100.0,
+                entropy.extra_rounds(),
+                generated.rounds
+            );
+            Some((
+                entropy.bits,
+                format!(
+                    ", {:.1} bits before the password policy",
+                    entropy.unconstrained_bits
+                ),
+            ))
+        }
+        Rendering::Charset(characters) => {
+            let _ = writeln!(
+                explanation,
+                "Password for {}: {} characters of a set of {}, picked from the final {} hash",
+                generated.domain,
+                generated.length,
+                characters.chars().collect::<BTreeSet<_>>().len(),
+                generated.algorithm
+            );
+            Some((
+                charset::entropy(characters, generated.length),
+                String::new(),
+            ))
+        }
+        Rendering::Passphrase(passphrase) => {
+            let _ = writeln!(
+                explanation,
+                "Passphrase for {}: {} words of the EFF large wordlist{}, picked with the final {} hash",
+                generated.domain,
+                passphrase.words,
+                if passphrase.digit { " and a digit" } else { "" },
+                generated.algorithm
+            );
+            Some((passphrase::entropy(passphrase), String::new()))
+        }
+        Rendering::Pin { reject_weak } => {
+            let _ = writeln!(
+                explanation,
+                "PIN for {}: {} digits picked from the final {} hash{}",
+                generated.domain,
+                generated.length,
+                generated.algorithm,
+                if *reject_weak {
+                    ", rejecting repeated, sequential and date-like PINs"
+                } else {
+                    ""
+                }
+            );
+            Some((pin::entropy(generated.length, *reject_weak), String::new()))
+        }
+        Rendering::Answer => {
+            let _ = writeln!(
+                explanation,
+                "Answer for {}: {} letters alternating consonants and vowels, picked with the final {} hash",
+                generated.domain, generated.length, generated.algorithm
+            );
+            Some((answer::entropy(generated.length), String::new()))
+        }
+        Rendering::Other => {
+            let _ = writeln!(
+                explanation,
+                "Password for {}: {} characters generated with the {} scheme, entropy not estimated",
+                generated.domain, generated.length, generated.scheme
+            );
+            None
+        }
+    };
+    let bits = match estimate {
+        Some((bits, note)) => {
+            let _ = writeln!(explanation, "  Entropy: {:.1} bits{}", bits, note);
+            bits
+        }
+        None => f64::INFINITY,
+    };
+
+    let Some(master) = master else {
+        let _ = writeln!(
+            explanation,
+            "Master password: held by the agent, not checked"
+        );
+        return explanation;
+    };
+    let _ = writeln!(
+        explanation,
+        "Master password: score {} of 4, about {:.1} bits, found offline in {}",
+        master.score, master.bits, master.crack_time
+    );
+    if master.score < MIN_SCORE {
+        let _ = writeln!(
+            explanation,
+            "  Warning: Weak master password, anyone knowing one generated password can find it"
+        );
+        if let Some(warning) = &master.warning {
+            let _ = writeln!(explanation, "  Warning: {}", warning);
+        }
+        for suggestion in &master.suggestions {
+            let _ = writeln!(explanation, "  Suggestion: {}", suggestion);
+        }
+    }
+    if master.bits < bits {
+        let _ = writeln!(
+            explanation,
+            "Passwords are no stronger than the master password, at most {:.1} bits",
+            master.bits
+        );
+    }
+    explanation
+}
+
+#[cfg(test)]
+mod test_explain {
+    use super::*;
+    use rustgenpass::passphrase::PassphraseConfig;
+
+    fn generated(length: usize, algorithm: &str) -> Generated {
+        Generated {
+            input: "example.com".to_string(),
+            domain: "example.com".to_string(),
+            scheme: "supergenpass".to_string(),
+            format: "password".to_string(),
+            algorithm: algorithm.to_string(),
+            rounds: 10,
+            length,
+            password: "jHMOHn7bRs".to_string(),
+            rendering: Rendering::Base64,
+        }
+    }
+
+    #[test]
+    fn explains_password_entropy() {
+        let explanation = explain(&generated(10, "md5"), None);
+        assert!(explanation.contains("10 characters of a base64 encoded md5 hash"));
+        assert!(explanation.contains("Entropy: 57.8 bits, 59.4 bits before the password policy"));
+        assert!(explanation.contains("34% of hashes accepted, 1.9 extra rounds on average"));
+        assert!(explanation.contains("held by the agent"));
+        assert!(!explanation.contains("jHMOHn7bRs"));
+    }
+
+    #[test]
+    fn skips_entropy_of_other_schemes() {
+        let generated = Generated {
+            scheme: "lesspass".to_string(),
+            rendering: Rendering::Other,
+            ..generated(16, "pbkdf2-sha256")
+        };
+        let explanation = explain(&generated, None);
+        assert!(explanation.contains("with the lesspass scheme, entropy not estimated"));
+        assert!(!explanation.contains("Entropy:"));
+    }
+
+    #[test]
+    fn explains_passphrase_entropy() {
+        let generated = Generated {
+            format: "passphrase".to_string(),
+            rendering: Rendering::Passphrase(PassphraseConfig {
+                words: 4,
+                digit: true,
+                ..PassphraseConfig::default()
+            }),
+            password: "freebie-destiny-saga-strung0".to_string(),
+            ..generated(28, "md5")
+        };
+        let explanation = explain(&generated, None);
+        assert!(explanation.contains("4 words of the EFF large wordlist and a digit"));
+        assert!(explanation.contains("Entropy: 57.0 bits"));
+        assert!(!explanation.contains("freebie"));
+    }
+
+    #[test]
+    fn explains_pin_entropy() {
+        let generated = Generated {
+            format: "pin".to_string(),
+            rendering: Rendering::Pin { reject_weak: true },
+            password: "3793".to_string(),
+            ..generated(4, "md5")
+        };
+        let explanation = explain(&generated, None);
+        assert!(explanation.contains("4 digits picked from the final md5 hash, rejecting"));
+        assert!(explanation.contains("Entropy: 13.2 bits"));
+        assert!(!explanation.contains("3793"));
+    }
+
+    #[test]
+    fn explains_answer_entropy() {
+        let generated = Generated {
+            format: "answer".to_string(),
+            rendering: Rendering::Answer,
+            password: "gomolekili".to_string(),
+            ..generated(10, "md5")
+        };
+        let explanation = explain(&generated, None);
+        assert!(explanation.contains("10 letters alternating consonants and vowels"));
+        assert!(explanation.contains("Entropy: 31.6 bits"));
+        assert!(!explanation.contains("gomolekili"));
+    }
+
+    #[test]
+    fn explains_charset_entropy() {
+        let generated = Generated {
+            rendering: Rendering::Charset("ABCDEFGHJKLMNPQRSTUVWXYZ23456789".to_string()),
+            password: "38JJZYHBHY".to_string(),
+            ..generated(10, "md5")
+        };
+        let explanation = explain(&generated, None);
+        assert!(
+            explanation.contains("10 characters of a set of 32, picked from the final md5 hash")
+        );
+        assert!(explanation.contains("Entropy: 49.9 bits"));
+        assert!(!explanation.contains("38JJZYHBHY"));
+    }
+
+    #[test]
+    fn warns_about_weak_master_password() {
+        let master = master_strength("examplepassword1", &["example.com"]);
+        let explanation = explain(&generated(16, "sha512"), Some(&master));
+        assert!(explanation.contains("Warning: Weak master password"));
+        assert!(explanation.contains("Passwords are no stronger than the master password"));
+        assert!(!explanation.contains("examplepassword1"));
+        assert!(!explanation.contains("jHMOHn7bRs"));
+    }
+
+    #[test]
+    fn accepts_strong_master_password() {
+        let master = master_strength("correct-horse-battery-staple-7Qx", &[]);
+        let explanation = explain(&generated(10, "md5"), Some(&master));
+        assert!(explanation.contains("score 4 of 4"));
+        assert!(!explanation.contains("Warning"));
+    }
+}
@@ -0,0 +1,313 @@
+//! Password generation as done by the binary, layering stored site settings
+//! over the configured defaults.
+
+#[cfg(unix)]
+use crate::agent;
+use crate::cli::{Cli, Command, Scheme};
+use crate::explain::{self, MasterStrength};
+use crate::options::SETTINGS;
+use crate::sites::{SiteError, SiteStore, default_sites_path};
+use rustgenpass::{
+    GeneratedPassword, HostnameConfig, fingerprint, get_hostname_with_config,
+    passphrase::PassphraseConfig,
+    passwordmaker::UrlComponents,
+    scheme::{self, Format, Params, Registry},
+};
+use serde::Serialize;
+use std::{collections::BTreeMap, error::Error};
+use zeroize::Zeroize;
+
+/// Generated password together with the parameters it was generated with.
+#[derive(Debug, Serialize)]
+pub struct Generated {
+    pub input: String,
+    pub domain: String,
+    pub scheme: String,
+    pub format: String,
+    pub algorithm: String,
+    pub rounds: u32,
+    pub length: usize,
+    pub password: String,
+    /// How the password was rendered from the final hash
+    #[serde(skip)]
+    pub rendering: Rendering,
+}
+
+/// How the password was rendered from the final SuperGenPass hash, if it
+/// was, with the options needed to explain its strength.
+#[derive(Debug)]
+pub enum Rendering {
+    /// Base64 encoded hash
+    Base64,
+    /// Characters of the set
+    Charset(String),
+    /// Words, separator, capitalization and digit of a passphrase
+    Passphrase(PassphraseConfig),
+    /// Digits of a PIN, rejecting weak PINs or not
+    Pin { reject_weak: bool },
+    /// Letters of the answer to a security question
+    Answer,
+    /// Password of another scheme
+    Other,
+}
+
+/// Parameters of the scheme the options in `cli` select, leaving out those
+/// it doesn't accept.
+pub fn scheme_params(cli: &Cli, scheme: &dyn scheme::Scheme) -> Params {
+    let mut params: Params = SETTINGS
+        .iter()
+        .filter_map(|setting| Some((setting.key.to_string(), (setting.get)(cli)?)))
+        .collect();
+    if let Some(Command::Answer { question }) = &cli.command {
+        params.insert("format".to_string(), Format::Answer.to_string());
+        params.insert("question".to_string(), question.clone());
+    }
+    params.retain(|key, _| scheme.params().contains(&key.as_str()));
+    params
+}
+
+/// Where the master password is held.
+enum Master {
+    Password(String),
+    #[cfg(unix)]
+    Agent(agent::Client),
+}
+
+/// Generates passwords for any number of domains with one master password.
+///
+/// The master password and decrypted secrets are wiped from memory when the
+/// generator is dropped.
+pub struct Generator {
+    master: Master,
+    store: SiteStore,
+    secrets: Option<BTreeMap<String, String>>,
+    schemes: Registry,
+    /// Master password and decrypted secrets are locked in memory
+    #[cfg(unix)]
+    locked: bool,
+}
+
+impl Generator {
+    /// Create a generator using the site store at its default location.
+    pub fn new(password: String) -> Result<Self, SiteError> {
+        Ok(Self::with_store(password, default_store()?))
+    }
+
+    /// Create a generator using the given site store.
+    pub fn with_store(password: String, store: SiteStore) -> Self {
+        Self {
+            master: Master::Password(password),
+            store,
+            secrets: None,
+            schemes: Registry::default(),
+            #[cfg(unix)]
+            locked: false,
+        }
+    }
+
+    /// Create a generator asking a running agent for the passwords.
+    #[cfg(unix)]
+    pub fn with_agent(client: agent::Client) -> Result<Self, SiteError> {
+        Ok(Self {
+            master: Master::Agent(client),
+            store: default_store()?,
+            secrets: None,
+            schemes: Registry::default(),
+            locked: false,
+        })
+    }
+
+    /// Lock the master password in memory, and the secrets of the sites once
+    /// they are decrypted.
+    #[cfg(unix)]
+    pub fn lock_memory(&mut self) {
+        self.locked = true;
+        if let Master::Password(password) = &self.master {
+            agent::lock_memory(password);
+        }
+        self.lock_secrets();
+    }
+
+    #[cfg(unix)]
+    fn lock_secrets(&self) {
+        if self.locked {
+            self.secrets
+                .iter()
+                .flat_map(BTreeMap::values)
+                .for_each(|secret| agent::lock_memory(secret));
+        }
+    }
+
+    /// Isolate the domain of the input and generate its password.
+    ///
+    /// Stored site settings override the options in `cli`, except for those
+    /// for which `from_command_line` returns true.
+    pub fn generate<F>(
+        &mut self,
+        input: &str,
+        mut cli: Cli,
+        from_command_line: F,
+    ) -> Result<Generated, Box<dyn Error>>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let domain = get_hostname_with_config(input, cli.clone().into())?;
+        let mut site_secret = false;
+        if let Some(profile) = self.store.sites.get(&domain) {
+            profile.apply(&mut cli, &from_command_line)?;
+            site_secret = profile.secret && !from_command_line("secret");
+        }
+        let algorithm = match cli.scheme {
+            Scheme::Supergenpass => cli.hash.to_string(),
+            Scheme::Pwdhash => "md5".to_string(),
+            Scheme::Spectre => "scrypt".to_string(),
+            Scheme::Lesspass => "pbkdf2-sha256".to_string(),
+            Scheme::Passwordmaker => cli.algorithm.to_string(),
+        };
+        // PasswordMaker picks the parts of the URL itself, the passthrough
+        // and keep subdomains options selecting which.
+        let domain = match cli.scheme {
+            Scheme::Passwordmaker => {
+                UrlComponents::from(&HostnameConfig::from(cli.clone())).text_to_use(input)
+            }
+            _ => domain,
+        };
+        let scheme = cli.scheme.to_string();
+        let format = match (&cli.scheme, &cli.command) {
+            (Scheme::Supergenpass, Some(Command::Answer { .. })) => Format::Answer,
+            (_, Some(Command::Answer { .. })) => {
+                return Err(format!(
+                    "Answers are only generated with the supergenpass scheme, not {}",
+                    cli.scheme
+                )
+                .into());
+            }
+            (Scheme::Supergenpass, _) => cli.format.clone(),
+            _ => Format::Password,
+        };
+        if cli.charset.is_some() {
+            match (&cli.scheme, &format) {
+                (Scheme::Supergenpass, Format::Password) | (Scheme::Passwordmaker, _) => {}
+                (Scheme::Supergenpass, format) => {
+                    return Err(
+                        format!("The character set isn't used by the {} format", format).into(),
+                    );
+                }
+                (scheme, _) => {
+                    return Err(
+                        format!("The character set isn't used by the {} scheme", scheme).into(),
+                    );
+                }
+            }
+        }
+        let rendering = match (&format, &cli.charset) {
+            _ if cli.scheme != Scheme::Supergenpass => Rendering::Other,
+            (Format::Password, Some(characters)) => Rendering::Charset(characters.clone()),
+            (Format::Password, None) => Rendering::Base64,
+            (Format::Passphrase, _) => Rendering::Passphrase(PassphraseConfig {
+                words: usize::from(cli.words),
+                separator: cli.separator.clone(),
+                capitalization: cli.capitalize.clone(),
+                digit: cli.digit,
+            }),
+            (Format::Pin, _) => Rendering::Pin {
+                reject_weak: cli.reject_weak,
+            },
+            (Format::Answer, _) => Rendering::Answer,
+        };
+        let params = scheme_params(
+            &cli,
+            self.schemes
+                .get(&scheme)
+                .map_err(|error| error.to_string())?,
+        );
+        let generated = self.generate_for_domain(&domain, &scheme, params, site_secret)?;
+        Ok(Generated {
+            input: input.to_string(),
+            domain,
+            scheme,
+            format: format.to_string(),
+            algorithm,
+            rounds: generated.rounds,
+            length: generated.password.chars().count(),
+            password: generated.password,
+            rendering,
+        })
+    }
+
+    /// Generate the password for an isolated domain with the named scheme,
+    /// using the secret stored for the site instead of the `secret`
+    /// parameter if `site_secret` is true.
+    pub fn generate_for_domain(
+        &mut self,
+        domain: &str,
+        scheme: &str,
+        mut params: Params,
+        site_secret: bool,
+    ) -> Result<GeneratedPassword, Box<dyn Error>> {
+        let password = match &self.master {
+            Master::Password(password) => password,
+            #[cfg(unix)]
+            Master::Agent(client) => {
+                return Ok(client.generate(domain, scheme, &params, site_secret)?);
+            }
+        };
+        let scheme = self
+            .schemes
+            .get(scheme)
+            .map_err(|error| error.to_string())?;
+        if site_secret && scheme.params().contains(&"secret") {
+            // Secrets are decrypted once, as the key derivation is slow on
+            // purpose.
+            if self.secrets.is_none() {
+                self.secrets = Some(self.store.secrets(password)?);
+                #[cfg(unix)]
+                self.lock_secrets();
+            }
+            match self.secrets.as_ref().and_then(|s| s.get(domain)) {
+                Some(secret) => params.insert("secret".to_string(), secret.clone()),
+                None => params.remove("secret"),
+            };
+        }
+        Ok(scheme
+            .generate(password, domain, &params)
+            .map_err(|error| error.to_string())?)
+    }
+
+    /// Fingerprint of the master password.
+    pub fn fingerprint(&self) -> Result<String, Box<dyn Error>> {
+        match &self.master {
+            Master::Password(password) => Ok(fingerprint(password.as_str())),
+            #[cfg(unix)]
+            Master::Agent(client) => Ok(client.fingerprint()?),
+        }
+    }
+
+    /// Estimated strength of the master password, unless it's held by an
+    /// agent.
+    pub fn master_strength(&self, user_inputs: &[&str]) -> Option<MasterStrength> {
+        match &self.master {
+            Master::Password(password) => Some(explain::master_strength(password, user_inputs)),
+            #[cfg(unix)]
+            Master::Agent(_) => None,
+        }
+    }
+}
+
+impl Drop for Generator {
+    fn drop(&mut self) {
+        if let Master::Password(password) = &mut self.master {
+            password.zeroize();
+        }
+        if let Some(secrets) = self.secrets.as_mut() {
+            secrets.values_mut().for_each(Zeroize::zeroize);
+        }
+    }
+}
+
+fn default_store() -> Result<SiteStore, SiteError> {
+    match default_sites_path() {
+        Some(path) => SiteStore::load(&path),
+        None => Ok(SiteStore::default()),
+    }
+}
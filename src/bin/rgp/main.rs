@@ -0,0 +1,433 @@
+#[cfg(unix)]
+mod agent;
+mod batch;
+mod cli;
+mod clipboard;
+#[cfg(unix)]
+mod daemon;
+mod explain;
+mod generator;
+mod native_host;
+mod options;
+mod output;
+mod password;
+mod qr;
+mod serve;
+mod settings;
+mod shell;
+mod sites;
+mod verify;
+
+use cli::{BatchFormat, Cli, Command, ConfigCommand, NativeHostCommand, SiteCommand};
+use clipboard::{ClipBackend, Clipboard, CommandClipboard};
+use generator::{Generated, Generator};
+use rustgenpass::get_hostname_with_config;
+use settings::Settings;
+use sites::{SiteStore, default_sites_path};
+use {
+    clap::{CommandFactory, FromArgMatches},
+    clap_complete::env::{CompleteEnv, Shells},
+    dialoguer::{Input, Password},
+    std::{
+        error::Error,
+        fs::{self, File},
+        io::{self, BufRead, BufReader},
+        path::PathBuf,
+        process, thread,
+        time::Duration,
+    },
+};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    CompleteEnv::with_factory(Cli::command).complete();
+    let matches = Cli::command().get_matches();
+    let settings = Settings::load(Cli::from_arg_matches(&matches)?, &matches)?;
+    match settings.cli.command.clone() {
+        None | Some(Command::Generate) | Some(Command::Answer { .. }) => generate(&settings),
+        Some(Command::Hostname { url }) => hostname(&settings.cli, url),
+        Some(Command::Verify {
+            expected,
+            search,
+            max_rounds,
+        }) => verify(&settings, expected, search.then_some(max_rounds)),
+        Some(Command::Fingerprint) => {
+            println!("{}", generator(&settings.cli)?.fingerprint()?);
+            Ok(())
+        }
+        Some(Command::Shell { idle_timeout }) => shell(&settings, idle_timeout),
+        #[cfg(unix)]
+        Some(Command::Agent {
+            socket,
+            lifetime,
+            foreground,
+        }) => agent(&settings, socket, lifetime, foreground),
+        Some(Command::NativeHost { command, .. }) => native_host(&settings, command),
+        Some(Command::Serve {
+            port,
+            #[cfg(unix)]
+            socket,
+            token,
+            token_file,
+        }) => {
+            #[cfg(not(unix))]
+            let socket = None;
+            serve(&settings, port, socket, token, token_file)
+        }
+        Some(Command::Completions { shell }) => {
+            let shells = Shells::builtins();
+            let completer = shells
+                .completer(&shell.to_string())
+                .ok_or("Unsupported shell")?;
+            completer.write_registration("COMPLETE", "rgp", "rgp", "rgp", &mut io::stdout())?;
+            Ok(())
+        }
+        Some(Command::Man) => {
+            clap_mangen::Man::new(Cli::command().name("rgp")).render(&mut io::stdout())?;
+            Ok(())
+        }
+        Some(Command::Config(ConfigCommand::Show)) => {
+            print!("{}", settings.show());
+            Ok(())
+        }
+        Some(Command::Site(command)) => site(&settings, command),
+        Some(Command::Batch { file, batch_format }) => batch(&settings, file, batch_format),
+    }
+}
+
+fn generate(settings: &Settings) -> Result<(), Box<dyn Error>> {
+    let input_domain = input_domain(&settings.cli)?;
+    let mut generator = generator(&settings.cli)?;
+    let generated = generator.generate(&input_domain, settings.cli.clone(), |id| {
+        settings.is_from_command_line(id)
+    })?;
+    let cli = &settings.cli;
+    if cli.explain {
+        let master = generator.master_strength(&[&generated.domain]);
+        eprint!("{}", explain::explain(&generated, master.as_ref()));
+    }
+    if cli.qr || cli.qr_file.is_some() {
+        show_qr(cli, &generated)?;
+    }
+    if cli.clip {
+        return clip(cli, &generated);
+    }
+    if !cli.qr && cli.qr_file.is_none() {
+        println!("{}", output::render(&generated, &cli.output));
+    }
+    Ok(())
+}
+
+/// Show the password as a QR code in the terminal or write it to a file,
+/// without printing the password itself.
+fn show_qr(cli: &Cli, generated: &Generated) -> Result<(), Box<dyn Error>> {
+    if let Some(path) = &cli.qr_file {
+        qr::write_file(&generated.password, path)?;
+        eprintln!(
+            "Wrote QR code of password for {} to {}",
+            generated.domain,
+            path.display()
+        );
+    }
+    if cli.qr {
+        eprintln!("QR code of password for {}:", generated.domain);
+        println!("{}", qr::render_terminal(&generated.password)?);
+    }
+    Ok(())
+}
+
+/// Copy the password to the clipboard, clearing it in the background after
+/// the timeout.
+fn clip(cli: &Cli, generated: &Generated) -> Result<(), Box<dyn Error>> {
+    let backend = cli
+        .clip_backend
+        .clone()
+        .or_else(ClipBackend::detect)
+        .ok_or("No clipboard command found, install wl-clipboard, xclip or xsel")?;
+    let clipboard = CommandClipboard::from(&backend);
+    clipboard.copy(&generated.password)?;
+    if cli.clip_timeout == 0 {
+        eprintln!("Copied password for {} to clipboard", generated.domain);
+        return Ok(());
+    }
+    eprintln!(
+        "Copied password for {} to clipboard, clearing in {} seconds",
+        generated.domain, cli.clip_timeout
+    );
+    #[cfg(unix)]
+    if daemon::daemonize()?.is_some() {
+        return Ok(());
+    }
+    thread::sleep(Duration::from_secs(cli.clip_timeout));
+    clipboard::clear_if_unchanged(&clipboard, &generated.password)?;
+    Ok(())
+}
+
+fn hostname(cli: &Cli, url: Option<String>) -> Result<(), Box<dyn Error>> {
+    let input_domain = match url {
+        Some(url) => url,
+        None => input_domain(cli)?,
+    };
+    println!(
+        "{}",
+        get_hostname_with_config(input_domain, cli.clone().into())?
+    );
+    Ok(())
+}
+
+fn verify(
+    settings: &Settings,
+    expected: Option<String>,
+    search_rounds: Option<u8>,
+) -> Result<(), Box<dyn Error>> {
+    let input_domain = input_domain(&settings.cli)?;
+    let expected = match expected {
+        Some(expected) => expected,
+        None => Password::new()
+            .with_prompt("Password to verify")
+            .interact()?,
+    };
+    if let Some(max_rounds) = search_rounds {
+        let password = master_password(&settings.cli, false)?;
+        let secret = settings.cli.secret.as_deref();
+        let matches = verify::search(&password, &input_domain, &expected, secret, max_rounds)?;
+        if matches.is_empty() {
+            eprintln!(
+                "No options up to {} rounds generate the password",
+                max_rounds
+            );
+            process::exit(1);
+        }
+        for found in matches {
+            println!("{}", found);
+        }
+        return Ok(());
+    }
+    let generated =
+        generator(&settings.cli)?.generate(&input_domain, settings.cli.clone(), |id| {
+            settings.is_from_command_line(id)
+        })?;
+    if generated.password != expected {
+        eprintln!("Password doesn't match for {}", generated.domain);
+        process::exit(1);
+    }
+    println!("Password matches for {}", generated.domain);
+    Ok(())
+}
+
+fn batch(
+    settings: &Settings,
+    file: Option<PathBuf>,
+    format: BatchFormat,
+) -> Result<(), Box<dyn Error>> {
+    let mut generator = generator(&settings.cli)?;
+    let reader: Box<dyn BufRead> = match file {
+        Some(path) if path.as_os_str() != "-" => Box::new(BufReader::new(File::open(path)?)),
+        _ => Box::new(io::stdin().lock()),
+    };
+    let succeeded = batch::run(
+        reader,
+        io::stdout().lock(),
+        &format,
+        &mut generator,
+        &settings.cli,
+        |id| settings.is_from_command_line(id),
+    )?;
+    if !succeeded {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+fn shell(settings: &Settings, idle_timeout: u64) -> Result<(), Box<dyn Error>> {
+    let generator = generator(&settings.cli)?;
+    eprintln!("Master password fingerprint: {}", generator.fingerprint()?);
+    let session = shell::Session::new(generator, settings.cli.clone(), |id: &str| {
+        settings.is_from_command_line(id)
+    });
+    let idle_timeout = (idle_timeout > 0).then(|| Duration::from_secs(idle_timeout));
+    shell::run(session, idle_timeout)?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn agent(
+    settings: &Settings,
+    socket: Option<PathBuf>,
+    lifetime: u64,
+    foreground: bool,
+) -> Result<(), Box<dyn Error>> {
+    let path = socket.unwrap_or_else(agent::default_socket_path);
+    let password = master_password(&settings.cli, false)?;
+    let mut generator = Generator::new(password)?;
+    let listener = agent::bind(&path)?;
+    let exports = format!(
+        "RGP_AGENT_SOCK={}; export RGP_AGENT_SOCK;",
+        output::shell_quote(&path.to_string_lossy())
+    );
+    if !foreground {
+        if let Some(pid) = daemon::daemonize()? {
+            println!("{}\necho Agent pid {};", exports, pid);
+            return Ok(());
+        }
+    } else {
+        println!("{}", exports);
+    }
+    generator.lock_memory();
+    let lifetime = (lifetime > 0).then(|| Duration::from_secs(lifetime));
+    agent::serve(listener, &path, generator, lifetime)?;
+    Ok(())
+}
+
+fn native_host(
+    settings: &Settings,
+    command: Option<NativeHostCommand>,
+) -> Result<(), Box<dyn Error>> {
+    match command {
+        Some(NativeHostCommand::Manifest {
+            browser,
+            path,
+            extension_ids,
+        }) => {
+            if !path.is_absolute() {
+                return Err("The path of the native messaging host must be absolute".into());
+            }
+            let manifest = native_host::manifest(&browser, &path, &extension_ids);
+            println!("{}", serde_json::to_string_pretty(&manifest)?);
+        }
+        None => native_host::run(
+            io::stdin().lock(),
+            io::stdout().lock(),
+            &settings.cli,
+            |id| settings.is_from_command_line(id),
+        )?,
+    }
+    Ok(())
+}
+
+fn serve(
+    settings: &Settings,
+    port: u16,
+    socket: Option<PathBuf>,
+    token: Option<String>,
+    token_file: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let token = match (token_file, token) {
+        (Some(path), _) => fs::read_to_string(path)?.trim().to_string(),
+        (None, Some(token)) => token,
+        (None, None) => {
+            let token = serve::random_token();
+            eprintln!("Token: {}", token);
+            token
+        }
+    };
+    if token.is_empty() {
+        return Err("The token must not be empty".into());
+    }
+    let generator = generator(&settings.cli)?;
+    let server = match socket {
+        #[cfg(unix)]
+        Some(path) => {
+            let server = tiny_http::Server::from_listener(agent::bind(&path)?, None)
+                .map_err(|err| err.to_string())?;
+            eprintln!("Listening on {}", path.display());
+            server
+        }
+        _ => {
+            let server =
+                tiny_http::Server::http(("127.0.0.1", port)).map_err(|err| err.to_string())?;
+            eprintln!("Listening on http://{}", server.server_addr());
+            server
+        }
+    };
+    let api = serve::Api::new(generator, settings.cli.clone(), token, |id| {
+        settings.is_from_command_line(id)
+    });
+    serve::run(server, api)
+}
+
+fn site(settings: &Settings, command: SiteCommand) -> Result<(), Box<dyn Error>> {
+    let cli = &settings.cli;
+    let path = sites_path()?;
+    let mut store = SiteStore::load(&path)?;
+    match command {
+        SiteCommand::List => {
+            for (domain, profile) in &store.sites {
+                println!("{} {}", domain, profile);
+            }
+            return Ok(());
+        }
+        SiteCommand::Set {
+            domain,
+            prompt_secret,
+        } => {
+            let profile = store.sites.entry(domain.clone()).or_default();
+            profile.store(cli, |id| settings.is_from_command_line(id));
+            let secret = match &cli.secret {
+                Some(secret) if settings.is_from_command_line("secret") => Some(secret.clone()),
+                _ if prompt_secret => Some(
+                    Password::new()
+                        .with_prompt(format!("Secret for {}", domain))
+                        .interact()?,
+                ),
+                _ => None,
+            };
+            if let Some(secret) = secret {
+                let password = master_password(cli, store.encrypted.is_none())?;
+                let mut secrets = store.secrets(&password)?;
+                secrets.insert(domain, secret);
+                store.set_secrets(&password, secrets)?;
+            }
+        }
+        SiteCommand::Remove { domain } => {
+            if store
+                .sites
+                .remove(&domain)
+                .is_some_and(|profile| profile.secret)
+            {
+                let password = master_password(cli, false)?;
+                let mut secrets = store.secrets(&password)?;
+                secrets.remove(&domain);
+                store.set_secrets(&password, secrets)?;
+            }
+        }
+    }
+    store.save(&path)?;
+    Ok(())
+}
+
+/// Domain / URL from the options, or prompted for.
+fn input_domain(cli: &Cli) -> Result<String, Box<dyn Error>> {
+    match &cli.domain {
+        Some(domain) => Ok(domain.clone()),
+        None => Ok(Input::new().with_prompt("Domain").interact()?),
+    }
+}
+
+/// Generator using the agent if one is set and the master password wasn't
+/// given non-interactively, otherwise reading or prompting for it.
+fn generator(cli: &Cli) -> Result<Generator, Box<dyn Error>> {
+    #[cfg(unix)]
+    if let Some(path) = &cli.agent_sock
+        && !password::is_selected(cli)
+    {
+        return Ok(Generator::with_agent(agent::Client::new(path.clone()))?);
+    }
+    Ok(Generator::new(master_password(cli, false)?)?)
+}
+
+/// Read the master password from the selected input channel, or prompt for it.
+fn master_password(cli: &Cli, confirm: bool) -> Result<String, Box<dyn Error>> {
+    if let Some(password) = password::read_password(cli)? {
+        return Ok(password);
+    }
+    let mut prompt = Password::new();
+    prompt.with_prompt("Enter master password");
+    if confirm {
+        prompt.with_confirmation("Confirm master password", "Passwords don't match");
+    }
+    Ok(prompt.interact()?)
+}
+
+fn sites_path() -> Result<PathBuf, Box<dyn Error>> {
+    default_sites_path().ok_or_else(|| "Couldn't determine configuration directory".into())
+}
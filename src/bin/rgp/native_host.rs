@@ -0,0 +1,183 @@
+//! Native messaging host for browser extensions.
+//!
+//! The browser starts the host and exchanges JSON messages over stdin and
+//! stdout, each preceded by its length as a 32 bit integer in native byte
+//! order. A request holds the URL, optionally the master password, and
+//! overrides for the options with the same keys as in batch mode:
+//!
+//! ```json
+//! {"url": "https://www.example.com/login", "password": "…", "length": 16}
+//! ```
+//!
+//! The response is the generated password in the format of `--output json`,
+//! or an object with an `error` message.
+
+#[cfg(unix)]
+use crate::agent;
+use crate::batch::apply_json_overrides;
+use crate::cli::Cli;
+use crate::generator::Generator;
+use clap::ValueEnum;
+use serde_json::{Value, json};
+use std::{
+    error::Error,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+/// Name of the host, which extensions connect to.
+pub const NAME: &str = "rustgenpass";
+
+/// Largest accepted message, requests are much smaller.
+const MAX_MESSAGE_LENGTH: u32 = 1024 * 1024;
+
+#[derive(Clone, Debug, ValueEnum)]
+/// Browsers with their own manifest format
+pub enum Browser {
+    /// Chrome, Chromium and other browsers based on it
+    Chrome,
+    Firefox,
+}
+
+/// Manifest registering the host with the browser, allowing the given
+/// extensions to connect to the executable at `path`.
+pub fn manifest(browser: &Browser, path: &Path, extension_ids: &[String]) -> Value {
+    let mut manifest = json!({
+        "name": NAME,
+        "description": "Generate passwords like rgp",
+        "path": path,
+        "type": "stdio",
+    });
+    manifest[match browser {
+        Browser::Chrome => "allowed_origins",
+        Browser::Firefox => "allowed_extensions",
+    }] = extension_ids
+        .iter()
+        .map(|id| match browser {
+            Browser::Chrome => format!("chrome-extension://{}/", id),
+            Browser::Firefox => id.clone(),
+        })
+        .collect();
+    manifest
+}
+
+/// Answer messages until the browser closes stdin.
+pub fn run<R, W, F>(mut reader: R, mut writer: W, cli: &Cli, from_command_line: F) -> io::Result<()>
+where
+    R: Read,
+    W: Write,
+    F: Fn(&str) -> bool,
+{
+    while let Some(message) = read_message(&mut reader)? {
+        let response = handle(&message, cli, &from_command_line)
+            .unwrap_or_else(|err| json!({ "error": err.to_string() }));
+        write_message(&mut writer, &response)?;
+    }
+    Ok(())
+}
+
+fn handle<F>(message: &[u8], cli: &Cli, from_command_line: F) -> Result<Value, Box<dyn Error>>
+where
+    F: Fn(&str) -> bool,
+{
+    let Value::Object(mut request) = serde_json::from_slice(message)? else {
+        return Err("Request must be an object".into());
+    };
+    let url = match request.remove("url") {
+        Some(Value::String(url)) => url,
+        _ => return Err("Request must have a url".into()),
+    };
+    let password = match request.remove("password") {
+        Some(Value::String(password)) => Some(password),
+        None => None,
+        _ => return Err("Invalid value for password".into()),
+    };
+    let (cli, overrides) = apply_json_overrides(request, cli)?;
+    let mut generator = generator(password, &cli)?;
+    let generated = generator.generate(&url, cli, |id| {
+        overrides.contains(&id) || from_command_line(id)
+    })?;
+    Ok(serde_json::to_value(generated)?)
+}
+
+/// Generator for the master password of the request, falling back to a
+/// running agent.
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn generator(password: Option<String>, cli: &Cli) -> Result<Generator, Box<dyn Error>> {
+    if let Some(password) = password {
+        return Ok(Generator::new(password)?);
+    }
+    #[cfg(unix)]
+    {
+        // Browsers don't pass on the environment of the shell the agent was
+        // started from, so the default socket is tried as well.
+        let path = cli
+            .agent_sock
+            .clone()
+            .unwrap_or_else(agent::default_socket_path);
+        if path.exists() {
+            return Ok(Generator::with_agent(agent::Client::new(path))?);
+        }
+    }
+    Err("Request must have a password when no agent is running".into())
+}
+
+fn read_message<R: Read>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut length = [0; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+    let length = u32::from_ne_bytes(length);
+    if length > MAX_MESSAGE_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Message too long",
+        ));
+    }
+    let mut message = vec![0; length as usize];
+    reader.read_exact(&mut message)?;
+    Ok(Some(message))
+}
+
+fn write_message<W: Write>(writer: &mut W, message: &Value) -> io::Result<()> {
+    let message = serde_json::to_vec(message)?;
+    writer.write_all(&(message.len() as u32).to_ne_bytes())?;
+    writer.write_all(&message)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod test_native_host {
+    use super::*;
+
+    #[test]
+    fn reads_framed_messages() {
+        let mut input = 2u32.to_ne_bytes().to_vec();
+        input.extend_from_slice(b"{}");
+        let mut reader = input.as_slice();
+        assert_eq!(Some(b"{}".to_vec()), read_message(&mut reader).unwrap());
+        assert_eq!(None, read_message(&mut reader).unwrap());
+    }
+
+    #[test]
+    fn rejects_oversized_messages() {
+        let input = (MAX_MESSAGE_LENGTH + 1).to_ne_bytes();
+        assert!(read_message(&mut input.as_slice()).is_err());
+    }
+
+    #[test]
+    fn creates_manifests() {
+        let path = Path::new("/usr/local/bin/rgp-native-host");
+        let chrome = manifest(&Browser::Chrome, path, &["abcdef".to_string()]);
+        assert_eq!(
+            json!(["chrome-extension://abcdef/"]),
+            chrome["allowed_origins"]
+        );
+        assert_eq!(json!("stdio"), chrome["type"]);
+        let firefox = manifest(&Browser::Firefox, path, &["rgp@example.com".to_string()]);
+        assert_eq!(json!(["rgp@example.com"]), firefox["allowed_extensions"]);
+        assert_eq!(json!("/usr/local/bin/rgp-native-host"), firefox["path"]);
+    }
+}
@@ -0,0 +1,567 @@
+//! Options that can be set outside of the command line, in one table.
+//!
+//! The configuration file, site profiles, overrides of batch mode and the
+//! parameters of the schemes all go through this table, so an option added
+//! here is available everywhere its scope allows.
+
+use crate::cli::{Cli, OutputFormat, Scheme};
+use crate::clipboard::ClipBackend;
+use clap::ValueEnum;
+use rustgenpass::{
+    HashAlgorithm,
+    passphrase::Capitalization,
+    passwordmaker::{Algorithm, LeetMode},
+    scheme::Format,
+    spectre::Template,
+};
+use std::{ops::RangeInclusive, path::PathBuf, str::FromStr};
+
+/// Where an option can be set besides the command line, the environment and
+/// the configuration file, each scope including the previous ones.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Scope {
+    /// Nowhere else
+    Config,
+    /// Overrides of a line in batch mode and the shell, and of requests to
+    /// `rgp serve` and the native host
+    Line,
+    /// Site profiles
+    Site,
+}
+
+/// Type of the value in TOML files.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Text,
+    Integer,
+    Boolean,
+}
+
+/// Option of the table.
+#[derive(Debug)]
+pub struct Setting {
+    /// Name in files and overrides, and of the scheme parameter
+    pub key: &'static str,
+    /// Id of the command line argument
+    pub id: &'static str,
+    pub kind: Kind,
+    pub scope: Scope,
+    /// Effective value, if set
+    pub get: fn(&Cli) -> Option<String>,
+    /// Set the option from its string value, `None` if it's invalid
+    pub set: fn(&mut Cli, &str) -> Option<()>,
+}
+
+impl Setting {
+    /// Effective value for display, hiding the secret.
+    pub fn display(&self, cli: &Cli) -> String {
+        match (self.get)(cli) {
+            Some(_) if self.key == "secret" => "********".to_string(),
+            Some(value) => value,
+            None => "<unset>".to_string(),
+        }
+    }
+
+    /// String value of a TOML value, if it is of the kind of the option.
+    pub fn read_toml(&self, value: &toml::Value) -> Option<String> {
+        match (self.kind, value) {
+            (Kind::Text, toml::Value::String(value)) => Some(value.clone()),
+            (Kind::Integer, toml::Value::Integer(value)) => Some(value.to_string()),
+            (Kind::Boolean, toml::Value::Boolean(value)) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+
+    /// TOML value of a string value, if it is of the kind of the option.
+    pub fn to_toml(&self, value: &str) -> Option<toml::Value> {
+        match self.kind {
+            Kind::Text => Some(toml::Value::String(value.to_string())),
+            Kind::Integer => value.parse().ok().map(toml::Value::Integer),
+            Kind::Boolean => value.parse().ok().map(toml::Value::Boolean),
+        }
+    }
+}
+
+/// Option named `key`, if it can be set in `scope`.
+pub fn find(key: &str, scope: Scope) -> Option<&'static Setting> {
+    SETTINGS
+        .iter()
+        .find(|setting| setting.key == key && setting.scope >= scope)
+}
+
+/// Every option, in the order they are shown.
+pub const SETTINGS: &[Setting] = &[
+    Setting {
+        key: "secret",
+        id: "secret",
+        kind: Kind::Text,
+        scope: Scope::Line,
+        get: |cli| cli.secret.clone(),
+        set: |cli, value| {
+            cli.secret = Some(value.to_string());
+            Some(())
+        },
+    },
+    Setting {
+        key: "domain",
+        id: "domain",
+        kind: Kind::Text,
+        scope: Scope::Config,
+        get: |cli| cli.domain.clone(),
+        set: |cli, value| {
+            cli.domain = Some(value.to_string());
+            Some(())
+        },
+    },
+    Setting {
+        key: "length",
+        id: "length",
+        kind: Kind::Integer,
+        scope: Scope::Site,
+        get: |cli| cli.length.map(|length| length.to_string()),
+        set: |cli, value| {
+            cli.length = Some(number(value, 4..=35)?);
+            Some(())
+        },
+    },
+    Setting {
+        key: "rounds",
+        id: "rounds",
+        kind: Kind::Integer,
+        scope: Scope::Site,
+        get: |cli| Some(cli.rounds.to_string()),
+        set: |cli, value| {
+            cli.rounds = value.parse().ok()?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "keep-subdomains",
+        id: "keep_subdomains",
+        kind: Kind::Boolean,
+        scope: Scope::Line,
+        get: |cli| Some(cli.keep_subdomains.to_string()),
+        set: |cli, value| {
+            cli.keep_subdomains = value.parse().ok()?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "passthrough",
+        id: "passthrough",
+        kind: Kind::Boolean,
+        scope: Scope::Line,
+        get: |cli| Some(cli.passthrough.to_string()),
+        set: |cli, value| {
+            cli.passthrough = value.parse().ok()?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "hash",
+        id: "hash",
+        kind: Kind::Text,
+        scope: Scope::Site,
+        get: |cli| Some(cli.hash.to_string()),
+        set: |cli, value| {
+            cli.hash = choice::<HashAlgorithm>(value)?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "scheme",
+        id: "scheme",
+        kind: Kind::Text,
+        scope: Scope::Site,
+        get: |cli| Some(cli.scheme.to_string()),
+        set: |cli, value| {
+            cli.scheme = choice::<Scheme>(value)?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "full-name",
+        id: "full_name",
+        kind: Kind::Text,
+        scope: Scope::Line,
+        get: |cli| cli.full_name.clone(),
+        set: |cli, value| {
+            cli.full_name = Some(value.to_string());
+            Some(())
+        },
+    },
+    Setting {
+        key: "counter",
+        id: "counter",
+        kind: Kind::Integer,
+        scope: Scope::Site,
+        get: |cli| Some(cli.counter.to_string()),
+        set: |cli, value| {
+            cli.counter = value.parse().ok()?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "template",
+        id: "template",
+        kind: Kind::Text,
+        scope: Scope::Site,
+        get: |cli| Some(cli.template.to_string()),
+        set: |cli, value| {
+            cli.template = choice::<Template>(value)?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "login",
+        id: "login",
+        kind: Kind::Text,
+        scope: Scope::Site,
+        get: |cli| cli.login.clone(),
+        set: |cli, value| {
+            cli.login = Some(value.to_string());
+            Some(())
+        },
+    },
+    Setting {
+        key: "no-lowercase",
+        id: "no_lowercase",
+        kind: Kind::Boolean,
+        scope: Scope::Site,
+        get: |cli| Some(cli.no_lowercase.to_string()),
+        set: |cli, value| {
+            cli.no_lowercase = value.parse().ok()?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "no-uppercase",
+        id: "no_uppercase",
+        kind: Kind::Boolean,
+        scope: Scope::Site,
+        get: |cli| Some(cli.no_uppercase.to_string()),
+        set: |cli, value| {
+            cli.no_uppercase = value.parse().ok()?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "no-digits",
+        id: "no_digits",
+        kind: Kind::Boolean,
+        scope: Scope::Site,
+        get: |cli| Some(cli.no_digits.to_string()),
+        set: |cli, value| {
+            cli.no_digits = value.parse().ok()?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "no-symbols",
+        id: "no_symbols",
+        kind: Kind::Boolean,
+        scope: Scope::Site,
+        get: |cli| Some(cli.no_symbols.to_string()),
+        set: |cli, value| {
+            cli.no_symbols = value.parse().ok()?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "algorithm",
+        id: "algorithm",
+        kind: Kind::Text,
+        scope: Scope::Site,
+        get: |cli| Some(cli.algorithm.to_string()),
+        set: |cli, value| {
+            cli.algorithm = choice::<Algorithm>(value)?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "charset",
+        id: "charset",
+        kind: Kind::Text,
+        scope: Scope::Site,
+        get: |cli| cli.charset.clone(),
+        set: |cli, value| {
+            cli.charset = Some(value.to_string());
+            Some(())
+        },
+    },
+    Setting {
+        key: "modifier",
+        id: "modifier",
+        kind: Kind::Text,
+        scope: Scope::Site,
+        get: |cli| cli.modifier.clone(),
+        set: |cli, value| {
+            cli.modifier = Some(value.to_string());
+            Some(())
+        },
+    },
+    Setting {
+        key: "prefix",
+        id: "prefix",
+        kind: Kind::Text,
+        scope: Scope::Site,
+        get: |cli| cli.prefix.clone(),
+        set: |cli, value| {
+            cli.prefix = Some(value.to_string());
+            Some(())
+        },
+    },
+    Setting {
+        key: "suffix",
+        id: "suffix",
+        kind: Kind::Text,
+        scope: Scope::Site,
+        get: |cli| cli.suffix.clone(),
+        set: |cli, value| {
+            cli.suffix = Some(value.to_string());
+            Some(())
+        },
+    },
+    Setting {
+        key: "leet",
+        id: "leet",
+        kind: Kind::Text,
+        scope: Scope::Site,
+        get: |cli| Some(cli.leet.to_string()),
+        set: |cli, value| {
+            cli.leet = choice::<LeetMode>(value)?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "leet-level",
+        id: "leet_level",
+        kind: Kind::Integer,
+        scope: Scope::Site,
+        get: |cli| Some(cli.leet_level.to_string()),
+        set: |cli, value| {
+            cli.leet_level = number(value, 1..=9)?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "format",
+        id: "format",
+        kind: Kind::Text,
+        scope: Scope::Site,
+        get: |cli| Some(cli.format.to_string()),
+        set: |cli, value| {
+            cli.format = choice::<Format>(value)?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "words",
+        id: "words",
+        kind: Kind::Integer,
+        scope: Scope::Site,
+        get: |cli| Some(cli.words.to_string()),
+        set: |cli, value| {
+            cli.words = number(value, 3..=20)?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "separator",
+        id: "separator",
+        kind: Kind::Text,
+        scope: Scope::Site,
+        get: |cli| Some(cli.separator.clone()),
+        set: |cli, value| {
+            cli.separator = value.to_string();
+            Some(())
+        },
+    },
+    Setting {
+        key: "capitalize",
+        id: "capitalize",
+        kind: Kind::Text,
+        scope: Scope::Site,
+        get: |cli| Some(cli.capitalize.to_string()),
+        set: |cli, value| {
+            cli.capitalize = choice::<Capitalization>(value)?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "digit",
+        id: "digit",
+        kind: Kind::Boolean,
+        scope: Scope::Site,
+        get: |cli| Some(cli.digit.to_string()),
+        set: |cli, value| {
+            cli.digit = value.parse().ok()?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "pin-length",
+        id: "pin_length",
+        kind: Kind::Integer,
+        scope: Scope::Site,
+        get: |cli| Some(cli.pin_length.to_string()),
+        set: |cli, value| {
+            cli.pin_length = number(value, 4..=8)?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "reject-weak",
+        id: "reject_weak",
+        kind: Kind::Boolean,
+        scope: Scope::Site,
+        get: |cli| Some(cli.reject_weak.to_string()),
+        set: |cli, value| {
+            cli.reject_weak = value.parse().ok()?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "output",
+        id: "output",
+        kind: Kind::Text,
+        scope: Scope::Config,
+        get: |cli| name(&cli.output),
+        set: |cli, value| {
+            cli.output = choice::<OutputFormat>(value)?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "qr",
+        id: "qr",
+        kind: Kind::Boolean,
+        scope: Scope::Config,
+        get: |cli| Some(cli.qr.to_string()),
+        set: |cli, value| {
+            cli.qr = value.parse().ok()?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "qr-file",
+        id: "qr_file",
+        kind: Kind::Text,
+        scope: Scope::Config,
+        get: |cli| cli.qr_file.as_ref().map(|path| path.display().to_string()),
+        set: |cli, value| {
+            cli.qr_file = Some(PathBuf::from(value));
+            Some(())
+        },
+    },
+    Setting {
+        key: "explain",
+        id: "explain",
+        kind: Kind::Boolean,
+        scope: Scope::Config,
+        get: |cli| Some(cli.explain.to_string()),
+        set: |cli, value| {
+            cli.explain = value.parse().ok()?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "clip",
+        id: "clip",
+        kind: Kind::Boolean,
+        scope: Scope::Config,
+        get: |cli| Some(cli.clip.to_string()),
+        set: |cli, value| {
+            cli.clip = value.parse().ok()?;
+            Some(())
+        },
+    },
+    Setting {
+        key: "clip-backend",
+        id: "clip_backend",
+        kind: Kind::Text,
+        scope: Scope::Config,
+        get: |cli| cli.clip_backend.as_ref().and_then(name),
+        set: |cli, value| {
+            cli.clip_backend = Some(choice::<ClipBackend>(value)?);
+            Some(())
+        },
+    },
+    Setting {
+        key: "clip-timeout",
+        id: "clip_timeout",
+        kind: Kind::Integer,
+        scope: Scope::Config,
+        get: |cli| Some(cli.clip_timeout.to_string()),
+        set: |cli, value| {
+            cli.clip_timeout = value.parse().ok()?;
+            Some(())
+        },
+    },
+];
+
+fn number<T: FromStr + PartialOrd>(value: &str, range: RangeInclusive<T>) -> Option<T> {
+    value.parse().ok().filter(|number| range.contains(number))
+}
+
+fn choice<T: ValueEnum>(value: &str) -> Option<T> {
+    T::from_str(value, true).ok()
+}
+
+fn name<T: ValueEnum>(value: &T) -> Option<String> {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+}
+
+#[cfg(test)]
+mod test_options {
+    use super::*;
+    use clap::{CommandFactory, Parser};
+
+    #[test]
+    fn covers_every_option_with_environment_variable() {
+        let command = Cli::command();
+        for arg in command.get_arguments() {
+            let id = arg.get_id().as_str();
+            if arg.get_env().is_none() || ["agent_sock", "config"].contains(&id) {
+                continue;
+            }
+            let setting = SETTINGS.iter().find(|setting| setting.id == id);
+            assert!(setting.is_some(), "{}", id);
+            assert_eq!(id.replace('_', "-"), setting.unwrap().key);
+            assert_eq!(
+                Some(format!("RGP_{}", id.to_uppercase()).as_ref()),
+                arg.get_env().and_then(|env| env.to_str())
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips_values() {
+        let cli = Cli::parse_from(["rgp", "--clip-backend", "xsel", "--login", "me"]);
+        for setting in SETTINGS {
+            let mut copy = Cli::parse_from(["rgp"]);
+            if let Some(value) = (setting.get)(&cli) {
+                assert_eq!(
+                    Some(()),
+                    (setting.set)(&mut copy, &value),
+                    "{}",
+                    setting.key
+                );
+                assert_eq!(Some(value.clone()), (setting.get)(&copy));
+                let toml = setting.to_toml(&value).unwrap();
+                assert_eq!(Some(value), setting.read_toml(&toml));
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_values_out_of_range() {
+        let mut cli = Cli::parse_from(["rgp"]);
+        let length = find("length", Scope::Site).unwrap();
+        assert_eq!(None, (length.set)(&mut cli, "40"));
+        assert_eq!(None, length.read_toml(&toml::Value::String("16".into())));
+        assert!(find("domain", Scope::Line).is_none());
+    }
+}
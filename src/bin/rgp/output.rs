@@ -0,0 +1,91 @@
+//! Rendering of a generated password for wrappers and scripts.
+//!
+//! The JSON object and the shell variables have the fields of [`Generated`]:
+//!
+//! * `input` - domain / URL as given
+//! * `domain` - isolated domain, or the parts of the URL used by passwordmaker
+//! * `scheme` - `supergenpass`, `pwdhash`, `spectre`, `lesspass` or
+//!   `passwordmaker`
+//! * `format` - `password`, `passphrase`, `pin` or `answer`, only supergenpass
+//!   renders other formats than `password`
+//! * `algorithm` - `md5` or `sha512` for supergenpass, `md5` for pwdhash,
+//!   `scrypt` for spectre, `pbkdf2-sha256` for lesspass and the selected
+//!   algorithm, e.g. `hmac-sha256`, for passwordmaker
+//! * `rounds` - hash rounds of supergenpass including those needed to satisfy
+//!   the password policy, `1` for the other schemes
+//! * `length` - number of characters of the password
+//! * `password` - generated password
+//!
+//! Shell variables use the `RGP_OUT_` prefix so evaluating them doesn't change
+//! the `RGP_*` defaults.
+
+use crate::cli::OutputFormat;
+use crate::generator::Generated;
+
+/// Render the generated password in the requested format.
+pub fn render(generated: &Generated, format: &OutputFormat) -> String {
+    match format {
+        OutputFormat::Plain => generated.password.clone(),
+        OutputFormat::Json => {
+            serde_json::to_string_pretty(generated).expect("Couldn't serialize output")
+        }
+        OutputFormat::Env => [
+            ("INPUT", generated.input.clone()),
+            ("DOMAIN", generated.domain.clone()),
+            ("SCHEME", generated.scheme.clone()),
+            ("FORMAT", generated.format.clone()),
+            ("ALGORITHM", generated.algorithm.clone()),
+            ("ROUNDS", generated.rounds.to_string()),
+            ("LENGTH", generated.length.to_string()),
+            ("PASSWORD", generated.password.clone()),
+        ]
+        .iter()
+        .map(|(name, value)| format!("RGP_OUT_{}={}", name, shell_quote(value)))
+        .collect::<Vec<_>>()
+        .join("\n"),
+    }
+}
+
+/// Quote a value for POSIX shells.
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod test_output {
+    use super::*;
+    use crate::generator::Rendering;
+
+    fn generated() -> Generated {
+        Generated {
+            input: "https://www.example.com/it's".to_string(),
+            domain: "example.com".to_string(),
+            scheme: "supergenpass".to_string(),
+            format: "password".to_string(),
+            algorithm: "md5".to_string(),
+            rounds: 10,
+            length: 10,
+            password: "jHMOHn7bRs".to_string(),
+            rendering: Rendering::Base64,
+        }
+    }
+
+    #[test]
+    fn renders_json() {
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&generated(), &OutputFormat::Json)).unwrap();
+        assert_eq!("example.com", json["domain"]);
+        assert_eq!("supergenpass", json["scheme"]);
+        assert_eq!("password", json["format"]);
+        assert_eq!(10, json["rounds"]);
+        assert_eq!("jHMOHn7bRs", json["password"]);
+    }
+
+    #[test]
+    fn renders_quoted_shell_variables() {
+        let output = render(&generated(), &OutputFormat::Env);
+        assert!(output.contains("RGP_OUT_INPUT='https://www.example.com/it'\\''s'\n"));
+        assert!(output.contains("RGP_OUT_SCHEME='supergenpass'\nRGP_OUT_FORMAT='password'\n"));
+        assert!(output.ends_with("RGP_OUT_PASSWORD='jHMOHn7bRs'"));
+    }
+}
@@ -0,0 +1,161 @@
+//! Non-interactive sources of the master password.
+//!
+//! Only the first line of the input is used, without its line ending, so
+//! passwords can be piped from `echo` or stored in files with a trailing
+//! newline.
+
+use crate::cli::Cli;
+use std::{
+    env, fmt, fs,
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+/// Read the master password from the channel selected on the command line,
+/// returning `None` if it should be prompted for.
+pub fn read_password(cli: &Cli) -> Result<Option<String>, PasswordError> {
+    let password = if let Some(password) = &cli.password {
+        password.clone()
+    } else if cli.password_stdin {
+        first_line(io::stdin().lock()).map_err(|err| PasswordError::Io("stdin".into(), err))?
+    } else if let Some(path) = &cli.password_file {
+        read_file(path)?
+    } else if let Some(fd) = cli.password_fd {
+        read_fd(fd)?
+    } else if let Some(var) = &cli.password_env {
+        env::var(var).map_err(|_| PasswordError::MissingEnv(var.clone()))?
+    } else {
+        return Ok(None);
+    };
+    if password.is_empty() {
+        return Err(PasswordError::Empty);
+    }
+    Ok(Some(password))
+}
+
+/// Whether a non-interactive source of the master password was selected.
+pub fn is_selected(cli: &Cli) -> bool {
+    cli.password.is_some()
+        || cli.password_stdin
+        || cli.password_file.is_some()
+        || cli.password_fd.is_some()
+        || cli.password_env.is_some()
+}
+
+fn read_file(path: &Path) -> Result<String, PasswordError> {
+    let io_error = |err| PasswordError::Io(path.to_path_buf(), err);
+    let file = BufReader::new(fs::File::open(path).map_err(io_error)?);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if file
+            .get_ref()
+            .metadata()
+            .map_err(io_error)?
+            .permissions()
+            .mode()
+            & 0o004
+            != 0
+        {
+            return Err(PasswordError::WorldReadable(path.to_path_buf()));
+        }
+    }
+    first_line(file).map_err(io_error)
+}
+
+fn read_fd(fd: u32) -> Result<String, PasswordError> {
+    // Reopening the descriptor through /dev/fd avoids taking ownership of a
+    // raw descriptor that might not be open.
+    let path = Path::new("/dev/fd").join(fd.to_string());
+    let io_error = |err| PasswordError::Io(path.clone(), err);
+    if cfg!(not(unix)) {
+        return Err(io_error(io::ErrorKind::Unsupported.into()));
+    }
+    let file = BufReader::new(fs::File::open(&path).map_err(io_error)?);
+    first_line(file).map_err(io_error)
+}
+
+/// Read the first line, leaving the rest of the input unconsumed so that
+/// stdin can carry further input after the password.
+fn first_line<R: BufRead>(mut reader: R) -> io::Result<String> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let line = line.strip_suffix('\n').unwrap_or(&line);
+    Ok(line.strip_suffix('\r').unwrap_or(line).to_string())
+}
+
+#[derive(Debug)]
+pub enum PasswordError {
+    Io(PathBuf, io::Error),
+    WorldReadable(PathBuf),
+    MissingEnv(String),
+    Empty,
+}
+
+impl fmt::Display for PasswordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PasswordError::Io(path, err) => {
+                write!(
+                    f,
+                    "Couldn't read master password from {}: {}",
+                    path.display(),
+                    err
+                )
+            }
+            PasswordError::WorldReadable(path) => write!(
+                f,
+                "Refusing to read master password from world-readable file {}",
+                path.display()
+            ),
+            PasswordError::MissingEnv(var) => {
+                write!(f, "Environment variable {} is not set", var)
+            }
+            PasswordError::Empty => write!(f, "Master password is empty"),
+        }
+    }
+}
+
+impl std::error::Error for PasswordError {}
+
+#[cfg(test)]
+mod test_password {
+    use super::*;
+
+    fn write_file(name: &str, contents: &str, mode: u32) -> PathBuf {
+        let path = env::temp_dir().join(format!("rgp-{}-{}", name, std::process::id()));
+        fs::write(&path, contents).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn strips_line_ending() {
+        assert_eq!(
+            "secret",
+            first_line("secret\r\nignored\n".as_bytes()).unwrap()
+        );
+        assert_eq!("secret", first_line("secret".as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn reads_private_file() {
+        let path = write_file("private", "masterpassword\n", 0o600);
+        let password = read_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!("masterpassword", password.unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_world_readable_file() {
+        let path = write_file("world-readable", "masterpassword\n", 0o644);
+        let password = read_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(password, Err(PasswordError::WorldReadable(_))));
+    }
+}
@@ -0,0 +1,161 @@
+//! QR codes of the generated password, for transferring it to a phone.
+//!
+//! The code is rendered in the terminal with half block characters, two
+//! modules per character, or written to a PNG or SVG file.
+
+use qrcode::{
+    Color, QrCode,
+    render::{svg, unicode::Dense1x2},
+    types::QrError,
+};
+use std::{
+    error::Error,
+    fmt, fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Pixels per module in PNG files.
+const PNG_SCALE: usize = 8;
+
+/// Modules of light border around the code, as required by the standard.
+const QUIET_ZONE: usize = 4;
+
+/// Render the code with half block characters.
+pub fn render_terminal(text: &str) -> Result<String, QrError> {
+    // Dark modules are drawn with the background, and light ones with the
+    // foreground, which shows the code correctly on dark terminals.
+    Ok(QrCode::new(text)?
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .build())
+}
+
+/// Write the code to a new PNG or SVG file, chosen by the file extension.
+pub fn write_file(text: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+    let code = QrCode::new(text)?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("svg") => {
+            create_file(path)?.write_all(
+                code.render::<svg::Color>()
+                    .min_dimensions(256, 256)
+                    .build()
+                    .as_bytes(),
+            )?;
+        }
+        Some(extension) if extension.eq_ignore_ascii_case("png") => {
+            write_png(&code, create_file(path)?)?
+        }
+        _ => return Err(QrFileError(path.display().to_string()).into()),
+    }
+    Ok(())
+}
+
+/// Create the file readable by the user only, as the code holds the
+/// password, refusing to overwrite an existing file.
+fn create_file(path: &Path) -> Result<fs::File, Box<dyn Error>> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path).map_err(|error| match error.kind() {
+        io::ErrorKind::AlreadyExists => {
+            format!("QR code file already exists: {}", path.display()).into()
+        }
+        _ => error.into(),
+    })
+}
+
+fn write_png(code: &QrCode, file: fs::File) -> io::Result<()> {
+    let modules = code.width();
+    let size = (modules + 2 * QUIET_ZONE) * PNG_SCALE;
+    let colors = code.to_colors();
+    let mut pixels = vec![0xff; size * size];
+    for (index, color) in colors.iter().enumerate() {
+        if *color == Color::Light {
+            continue;
+        }
+        let x = (index % modules + QUIET_ZONE) * PNG_SCALE;
+        let y = (index / modules + QUIET_ZONE) * PNG_SCALE;
+        for row in y..y + PNG_SCALE {
+            pixels[row * size + x..row * size + x + PNG_SCALE].fill(0);
+        }
+    }
+    let mut encoder = png::Encoder::new(BufWriter::new(file), size as u32, size as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&pixels).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)
+}
+
+/// File extension not matching a supported image format.
+#[derive(Debug)]
+pub struct QrFileError(String);
+
+impl fmt::Display for QrFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "QR code files must end with .png or .svg: {}", self.0)
+    }
+}
+
+impl std::error::Error for QrFileError {}
+
+#[cfg(test)]
+mod test_qr {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn renders_half_blocks() {
+        let code = render_terminal("izHhm22SMfZeg8Q3t2BrZgAA").unwrap();
+        assert!(!code.contains("izHhm22SMfZeg8Q3t2BrZgAA"));
+        assert!(
+            code.chars()
+                .all(|c| [' ', '\u{2580}', '\u{2584}', '\u{2588}', '\n'].contains(&c))
+        );
+        // Version 2 code of 25 modules with the quiet zone on both sides.
+        let lines: Vec<&str> = code.lines().collect();
+        assert_eq!(33, lines[0].chars().count());
+        assert_eq!(17, lines.len());
+    }
+
+    #[test]
+    fn writes_image_files() {
+        let directory = env::temp_dir();
+        let png = directory.join(format!("rgp-qr-{}.png", std::process::id()));
+        write_file("jHMOHn7bRs", &png).unwrap();
+        assert!(fs::read(&png).unwrap().starts_with(b"\x89PNG\r\n\x1a\n"));
+        fs::remove_file(&png).unwrap();
+
+        let svg = directory.join(format!("rgp-qr-{}.SVG", std::process::id()));
+        write_file("jHMOHn7bRs", &svg).unwrap();
+        assert!(fs::read_to_string(&svg).unwrap().contains("<svg"));
+        fs::remove_file(&svg).unwrap();
+    }
+
+    #[test]
+    fn writes_private_new_files() {
+        let path = env::temp_dir().join(format!("rgp-qr-private-{}.png", std::process::id()));
+        let _ = fs::remove_file(&path);
+        write_file("jHMOHn7bRs", &path).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(0o600, mode & 0o777);
+        }
+        let written = fs::read(&path).unwrap();
+        assert!(write_file("izHhm22SMfZeg8Q3t2BrZgAA", &path).is_err());
+        assert_eq!(written, fs::read(&path).unwrap());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_unknown_file_types() {
+        let path = env::temp_dir().join("rgp-qr.txt");
+        assert!(write_file("jHMOHn7bRs", &path).is_err());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,231 @@
+//! Local HTTP API for tools generating passwords over HTTP.
+//!
+//! Requests and responses are JSON objects. Every endpoint except
+//! `GET /health` requires the token in an `Authorization: Bearer` header.
+//!
+//! * `POST /generate` takes a `url` and overrides for the options with the
+//!   same keys as in batch mode, and responds in the format of
+//!   `--output json`
+//! * `POST /hostname` takes a `url` and the `keep-subdomains` and
+//!   `passthrough` options, and responds with the isolated `domain`
+//! * `GET /health` responds with `{"status": "ok"}`
+//!
+//! Errors are responded to with an `error` message.
+
+use crate::batch::apply_json_overrides;
+use crate::cli::Cli;
+use crate::generator::Generator;
+use chacha20poly1305::aead::{OsRng, rand_core::RngCore};
+use rustgenpass::get_hostname_with_config;
+use serde_json::{Map, Value, json};
+use std::{error::Error, io::Read};
+use tiny_http::{Header, Request, Response, Server};
+
+/// Largest accepted request body, requests are much smaller.
+const MAX_BODY_LENGTH: u64 = 64 * 1024;
+
+/// State of the API, owning the master password through the generator.
+pub struct Api<F> {
+    generator: Generator,
+    cli: Cli,
+    token: String,
+    from_command_line: F,
+}
+
+impl<F> Api<F>
+where
+    F: Fn(&str) -> bool,
+{
+    pub fn new(generator: Generator, cli: Cli, token: String, from_command_line: F) -> Self {
+        Self {
+            generator,
+            cli,
+            token,
+            from_command_line,
+        }
+    }
+
+    /// Respond to a request with a status code and a JSON body.
+    pub fn respond(
+        &mut self,
+        method: &str,
+        url: &str,
+        authorization: Option<&str>,
+        body: &[u8],
+    ) -> (u16, Value) {
+        let path = url.split_once('?').map_or(url, |(path, _)| path);
+        if path == "/health" {
+            return match method {
+                "GET" => (200, json!({ "status": "ok" })),
+                _ => error(405, "Method not allowed"),
+            };
+        }
+        if !matches!(path, "/generate" | "/hostname") {
+            return error(404, "Not found");
+        }
+        if method != "POST" {
+            return error(405, "Method not allowed");
+        }
+        if !self.is_authorized(authorization) {
+            return error(401, "Missing or invalid bearer token");
+        }
+        let result = match path {
+            "/generate" => self.generate(body),
+            _ => self.hostname(body),
+        };
+        match result {
+            Ok(response) => (200, response),
+            Err(err) => error(400, &err.to_string()),
+        }
+    }
+
+    fn generate(&mut self, body: &[u8]) -> Result<Value, Box<dyn Error>> {
+        let (url, request) = parse_request(body)?;
+        let (cli, overrides) = apply_json_overrides(request, &self.cli)?;
+        let generated = self.generator.generate(&url, cli, |id| {
+            overrides.contains(&id) || (self.from_command_line)(id)
+        })?;
+        Ok(serde_json::to_value(generated)?)
+    }
+
+    fn hostname(&self, body: &[u8]) -> Result<Value, Box<dyn Error>> {
+        let (url, request) = parse_request(body)?;
+        let (cli, _) = apply_json_overrides(request, &self.cli)?;
+        let domain = get_hostname_with_config(url, cli.into())?;
+        Ok(json!({ "domain": domain }))
+    }
+
+    /// Compare the token in constant time, so its contents can't be guessed
+    /// from response times.
+    fn is_authorized(&self, authorization: Option<&str>) -> bool {
+        let Some(token) = authorization.and_then(|value| value.strip_prefix("Bearer ")) else {
+            return false;
+        };
+        token.len() == self.token.len()
+            && token
+                .bytes()
+                .zip(self.token.bytes())
+                .fold(0, |difference, (a, b)| difference | (a ^ b))
+                == 0
+    }
+}
+
+/// Random token for when none is configured.
+pub fn random_token() -> String {
+    let mut token = [0; 24];
+    OsRng.fill_bytes(&mut token);
+    token.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Serve requests one at a time until the process is stopped.
+pub fn run<F>(server: Server, mut api: Api<F>) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str) -> bool,
+{
+    for mut request in server.incoming_requests() {
+        let mut body = Vec::new();
+        let (status, response) = match request
+            .as_reader()
+            .take(MAX_BODY_LENGTH + 1)
+            .read_to_end(&mut body)
+        {
+            Ok(length) if length as u64 > MAX_BODY_LENGTH => error(413, "Request too large"),
+            Ok(_) => api.respond(
+                request.method().as_str(),
+                request.url(),
+                header(&request, "Authorization"),
+                &body,
+            ),
+            Err(err) => error(400, &err.to_string()),
+        };
+        let mut response = Response::from_data(response.to_string())
+            .with_status_code(status)
+            .with_header(
+                Header::from_bytes("Content-Type", "application/json").expect("Invalid header"),
+            );
+        if status == 401 {
+            response.add_header(
+                Header::from_bytes("WWW-Authenticate", "Bearer").expect("Invalid header"),
+            );
+        }
+        // A client going away doesn't affect the others.
+        let _ = request.respond(response);
+    }
+    Ok(())
+}
+
+fn header<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.as_str())
+}
+
+/// Split a request body into the URL and the remaining fields.
+fn parse_request(body: &[u8]) -> Result<(String, Map<String, Value>), Box<dyn Error>> {
+    let Value::Object(mut request) = serde_json::from_slice(body)? else {
+        return Err("Request must be an object".into());
+    };
+    match request.remove("url") {
+        Some(Value::String(url)) => Ok((url, request)),
+        _ => Err("Request must have a url".into()),
+    }
+}
+
+fn error(status: u16, message: &str) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+#[cfg(test)]
+mod test_serve {
+    use super::*;
+    use crate::sites::SiteStore;
+    use clap::Parser;
+
+    fn api() -> Api<fn(&str) -> bool> {
+        let generator = Generator::with_store("masterpassword".to_string(), SiteStore::default());
+        Api::new(
+            generator,
+            Cli::parse_from(["rgp"]),
+            "token".to_string(),
+            |_| false,
+        )
+    }
+
+    #[test]
+    fn requires_token() {
+        let body = br#"{"url": "example.com"}"#;
+        assert_eq!(401, api().respond("POST", "/generate", None, body).0);
+        assert_eq!(
+            401,
+            api()
+                .respond("POST", "/generate", Some("Bearer tokem"), body)
+                .0
+        );
+        assert_eq!(
+            401,
+            api().respond("POST", "/hostname", Some("token"), body).0
+        );
+        assert_eq!(200, api().respond("GET", "/health", None, b"").0);
+    }
+
+    #[test]
+    fn rejects_unknown_routes() {
+        assert_eq!(404, api().respond("GET", "/", Some("Bearer token"), b"").0);
+        assert_eq!(
+            405,
+            api()
+                .respond("GET", "/generate", Some("Bearer token"), b"")
+                .0
+        );
+        assert_eq!(405, api().respond("POST", "/health", None, b"").0);
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        let (status, response) = api().respond("POST", "/generate", Some("Bearer token"), b"[]");
+        assert_eq!(400, status);
+        assert_eq!(json!("Request must be an object"), response["error"]);
+    }
+}
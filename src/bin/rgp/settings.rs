@@ -0,0 +1,274 @@
+//! Layered defaults for the options of the `rgp` binary.
+//!
+//! Each option is resolved with the precedence command line, `RGP_*`
+//! environment variable, configuration file and finally the built-in default.
+
+use crate::cli::Cli;
+use crate::options::{self, SETTINGS, Scope, Setting};
+use clap::{ArgMatches, parser::ValueSource};
+use std::{collections::BTreeMap, env, fmt, fs, io, path::PathBuf};
+
+/// Where the effective value of an option came from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    CommandLine,
+    Environment(String),
+    File(PathBuf),
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::CommandLine => write!(f, "command line"),
+            Source::Environment(var) => write!(f, "environment {}", var),
+            Source::File(path) => write!(f, "file {}", path.display()),
+            Source::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// Contents of the configuration file, every key is optional.
+type ConfigFile = BTreeMap<String, toml::Value>;
+
+/// Command line options with defaults from environment and configuration
+/// file applied, together with the source of each option.
+#[derive(Debug)]
+pub struct Settings {
+    pub cli: Cli,
+    pub config_path: Option<PathBuf>,
+    pub config_loaded: bool,
+    pub sources: Vec<(&'static Setting, Source)>,
+}
+
+impl Settings {
+    /// Resolve the effective settings from parsed arguments.
+    pub fn load(mut cli: Cli, matches: &ArgMatches) -> Result<Self, SettingsError> {
+        let explicit_path = cli.config.clone();
+        let config_path = explicit_path.clone().or_else(default_config_path);
+        let file = match &config_path {
+            Some(path) => match fs::read_to_string(path) {
+                Ok(contents) => Some(
+                    toml::from_str::<ConfigFile>(&contents)
+                        .map_err(|err| SettingsError::Parse(path.clone(), err))?,
+                ),
+                Err(err) if err.kind() == io::ErrorKind::NotFound && explicit_path.is_none() => {
+                    None
+                }
+                Err(err) => return Err(SettingsError::Read(path.clone(), err)),
+            },
+            None => None,
+        };
+        let config_loaded = file.is_some();
+        let mut file = file.unwrap_or_default();
+        let file_path = config_path.clone().unwrap_or_default();
+
+        let mut sources = Vec::new();
+        for setting in SETTINGS {
+            let source = match matches.value_source(setting.id) {
+                Some(ValueSource::CommandLine) => Source::CommandLine,
+                Some(ValueSource::EnvVariable) => {
+                    Source::Environment(format!("RGP_{}", setting.id.to_uppercase()))
+                }
+                _ if file.contains_key(setting.key) => Source::File(file_path.clone()),
+                _ => Source::Default,
+            };
+            if let (Source::File(_), Some(value)) = (&source, file.remove(setting.key)) {
+                setting
+                    .read_toml(&value)
+                    .and_then(|string| (setting.set)(&mut cli, &string))
+                    .ok_or_else(|| SettingsError::Invalid(setting.key, value.to_string()))?;
+            }
+            sources.push((setting, source));
+        }
+        if let Some(key) = file
+            .into_keys()
+            .find(|key| options::find(key, Scope::Config).is_none())
+        {
+            return Err(SettingsError::Unknown(file_path, key));
+        }
+
+        Ok(Self {
+            cli,
+            config_path,
+            config_loaded,
+            sources,
+        })
+    }
+
+    /// Where the option with the argument id `id` came from.
+    pub fn source(&self, id: &str) -> Option<&Source> {
+        self.sources
+            .iter()
+            .find(|(setting, _)| setting.id == id)
+            .map(|(_, source)| source)
+    }
+
+    /// Whether the option was given on the command line.
+    pub fn is_from_command_line(&self, id: &str) -> bool {
+        self.source(id) == Some(&Source::CommandLine)
+    }
+
+    /// Render the effective settings and their sources, hiding the secret.
+    pub fn show(&self) -> String {
+        let mut output = match &self.config_path {
+            Some(path) if self.config_loaded => format!("config: {}\n", path.display()),
+            Some(path) => format!("config: {} (not found)\n", path.display()),
+            None => "config: none\n".to_string(),
+        };
+        for (setting, source) in &self.sources {
+            output.push_str(&format!(
+                "{} = {} ({})\n",
+                setting.key,
+                setting.display(&self.cli),
+                source
+            ));
+        }
+        output
+    }
+}
+
+/// Default location of the configuration file, following the XDG base
+/// directory specification.
+pub fn default_config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+/// Directory holding the files of `rgp`.
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .map(|dir| dir.join("rgp"))
+}
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Invalid(&'static str, String),
+    Unknown(PathBuf, String),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SettingsError::Read(path, err) => {
+                write!(f, "Couldn't read config {}: {}", path.display(), err)
+            }
+            SettingsError::Parse(path, err) => {
+                write!(f, "Invalid config {}: {}", path.display(), err)
+            }
+            SettingsError::Invalid(key, value) => {
+                write!(f, "Invalid value for {} in config: {}", key, value)
+            }
+            SettingsError::Unknown(path, key) => {
+                write!(f, "Unknown key {} in config {}", key, path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+#[cfg(test)]
+mod test_settings {
+    use super::*;
+    use crate::clipboard::ClipBackend;
+    use clap::{CommandFactory, FromArgMatches};
+    use rustgenpass::HashAlgorithm;
+
+    fn load(name: &str, args: &[&str], config: &str) -> Result<Settings, SettingsError> {
+        let path = env::temp_dir().join(format!("rgp-{}-{}.toml", name, std::process::id()));
+        fs::write(&path, config).unwrap();
+        let mut argv = vec!["rgp", "--config", path.to_str().unwrap()];
+        argv.extend_from_slice(args);
+        let matches = Cli::command().try_get_matches_from(argv).unwrap();
+        let cli = Cli::from_arg_matches(&matches).unwrap();
+        let settings = Settings::load(cli, &matches);
+        fs::remove_file(&path).unwrap();
+        settings
+    }
+
+    #[test]
+    fn uses_built_in_defaults() {
+        let settings = load("defaults", &[], "").unwrap();
+        assert_eq!(None, settings.cli.length);
+        assert_eq!(Some(&Source::Default), settings.source("length"));
+    }
+
+    #[test]
+    fn file_overrides_defaults() {
+        let settings = load("file", &[], "length = 16\nrounds = 50\nhash = \"sha512\"\n").unwrap();
+        assert_eq!(Some(16), settings.cli.length);
+        assert_eq!(50, settings.cli.rounds);
+        assert!(matches!(settings.cli.hash, HashAlgorithm::SHA512));
+        assert!(matches!(settings.source("rounds"), Some(Source::File(_))));
+    }
+
+    #[test]
+    fn command_line_overrides_file() {
+        let settings = load("cli", &["--length", "12"], "length = 16").unwrap();
+        assert_eq!(Some(12), settings.cli.length);
+        assert_eq!(Some(&Source::CommandLine), settings.source("length"));
+    }
+
+    #[test]
+    fn reads_clipboard_settings_from_file() {
+        let settings = load(
+            "clip",
+            &[],
+            "clip = true\nclip-backend = \"xsel\"\nclip-timeout = 10\n",
+        )
+        .unwrap();
+        assert!(settings.cli.clip);
+        assert!(matches!(settings.cli.clip_backend, Some(ClipBackend::Xsel)));
+        assert_eq!(10, settings.cli.clip_timeout);
+    }
+
+    #[test]
+    fn reads_qr_settings_from_file() {
+        let settings = load("qr", &[], "qr = true\nqr-file = \"password.svg\"\n").unwrap();
+        assert!(settings.cli.qr);
+        assert_eq!(Some(PathBuf::from("password.svg")), settings.cli.qr_file);
+        assert!(settings.show().contains("\nqr-file = password.svg (file "));
+    }
+
+    #[test]
+    fn reads_explain_from_file() {
+        let settings = load("explain", &[], "explain = true\n").unwrap();
+        assert!(settings.cli.explain);
+        assert!(matches!(settings.source("explain"), Some(Source::File(_))));
+    }
+
+    #[test]
+    fn rejects_invalid_length_in_file() {
+        assert!(load("invalid", &[], "length = 40").is_err());
+    }
+
+    #[test]
+    fn rejects_value_of_wrong_type() {
+        assert!(load("type", &[], "length = \"16\"").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(matches!(
+            load("unknown", &[], "lenght = 16"),
+            Err(SettingsError::Unknown(_, key)) if key == "lenght"
+        ));
+    }
+
+    #[test]
+    fn shows_every_option() {
+        let settings = load("show", &["--format", "pin"], "login = \"me\"").unwrap();
+        let show = settings.show();
+        assert!(show.contains("\nformat = pin (command line)\n"));
+        assert!(show.contains("\nlogin = me (file "));
+        assert!(show.contains("\npin-length = 4 (default)\n"));
+        assert!(show.contains("\nsecret = <unset> (default)\n"));
+        assert_eq!(1 + SETTINGS.len(), show.lines().count());
+    }
+}
@@ -0,0 +1,273 @@
+//! Interactive session generating passwords with a cached master password.
+//!
+//! Each line is a domain / URL, optionally followed by overrides like in
+//! batch mode, or a command starting with `:`. The master password is wiped
+//! when the session ends, including after being idle for too long.
+
+use crate::batch::{apply_override, parse_line};
+use crate::cli::Cli;
+use crate::generator::Generator;
+use crate::options::SETTINGS;
+use crate::output;
+use std::{
+    io::{self, BufRead, IsTerminal, Write},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+const HELP: &str = "\
+Enter a domain / URL to generate its password, optionally followed by
+overrides for that line only, e.g. `example.com length=16`.
+
+Commands:
+  :length <4-35>             Set the password length
+  :rounds <N>                Set the number of hash rounds
+  :hash <md5|sha512>         Set the hashing method
+  :scheme <SCHEME>           Set the scheme, supergenpass, pwdhash, spectre,
+                             lesspass or passwordmaker
+  :full-name <NAME>          Set the full name, for spectre
+  :counter <N>               Set the counter, for spectre and lesspass
+  :template <TEMPLATE>       Set the template class, for spectre
+  :login <LOGIN>             Set the login, for lesspass and passwordmaker
+  :no-lowercase <BOOL>       Set whether lowercase letters are left out
+  :no-uppercase <BOOL>       Set whether uppercase letters are left out
+  :no-digits <BOOL>          Set whether digits are left out
+  :no-symbols <BOOL>         Set whether symbols are left out
+  :algorithm <ALGORITHM>     Set the hash algorithm, for passwordmaker
+  :charset <CHARACTERS>      Set the characters of the password
+  :modifier <MODIFIER>       Set the modifier, for passwordmaker
+  :prefix <PREFIX>           Set the prefix, for passwordmaker
+  :suffix <SUFFIX>           Set the suffix, for passwordmaker
+  :leet <MODE>               Set when l33t is applied, for passwordmaker
+  :leet-level <1-9>          Set the level of l33t, for passwordmaker
+  :format <FORMAT>           Set the format, password, passphrase or pin
+  :words <3-20>              Set the number of words, for passphrases
+  :separator <SEPARATOR>     Set the separator of words, for passphrases
+  :capitalize <CASE>         Set the capitalization, lower, title or upper
+  :digit <BOOL>              Set whether a digit is added, for passphrases
+  :pin-length <4-8>          Set the number of digits, for PINs
+  :reject-weak <BOOL>        Set whether weak PINs are rejected
+  :secret <SECRET>           Set the secret added to the master password
+  :keep-subdomains <BOOL>    Set whether subdomains are kept
+  :passthrough <BOOL>        Set whether the domain is passed through
+  :show                      Print the current settings
+  :help                      Print this help
+  :quit                      End the session";
+
+/// Result of handling a line.
+#[derive(Debug, PartialEq)]
+pub enum Step {
+    Print(String),
+    Continue,
+    Quit,
+}
+
+/// Session state, owning the master password through the generator.
+pub struct Session<F> {
+    generator: Generator,
+    cli: Cli,
+    overrides: Vec<&'static str>,
+    from_command_line: F,
+}
+
+impl<F> Session<F>
+where
+    F: Fn(&str) -> bool,
+{
+    pub fn new(generator: Generator, cli: Cli, from_command_line: F) -> Self {
+        Self {
+            generator,
+            cli,
+            overrides: Vec::new(),
+            from_command_line,
+        }
+    }
+
+    /// Handle a line of input.
+    ///
+    /// Settings changed with commands override stored site settings, like
+    /// options given on the command line.
+    pub fn handle(&mut self, line: &str) -> Result<Step, String> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(Step::Continue);
+        }
+        let Some(command) = line.strip_prefix(':') else {
+            let (input, cli, overrides) = parse_line(line, &self.cli)?;
+            let generated = self
+                .generator
+                .generate(&input, cli, |id| {
+                    overrides.contains(&id)
+                        || self.overrides.contains(&id)
+                        || (self.from_command_line)(id)
+                })
+                .map_err(|err| err.to_string())?;
+            return Ok(Step::Print(output::render(&generated, &self.cli.output)));
+        };
+        let (name, value) = command
+            .split_once(char::is_whitespace)
+            .map(|(name, value)| (name, Some(value.trim())))
+            .unwrap_or((command, None));
+        match (name, value) {
+            ("quit" | "exit", None) => Ok(Step::Quit),
+            ("help", None) => Ok(Step::Print(HELP.to_string())),
+            ("show", None) => Ok(Step::Print(self.show())),
+            (name, Some(value)) => {
+                let id = apply_override(&mut self.cli, name, value)?;
+                if !self.overrides.contains(&id) {
+                    self.overrides.push(id);
+                }
+                Ok(Step::Continue)
+            }
+            _ => Err(format!("Unknown command: {}, see :help", line)),
+        }
+    }
+
+    fn show(&self) -> String {
+        SETTINGS
+            .iter()
+            .map(|setting| format!("{} = {}", setting.key, setting.display(&self.cli)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Run the session on stdin until it is quit, stdin is closed or no line
+/// is entered for `idle_timeout`.
+pub fn run<F>(mut session: Session<F>, idle_timeout: Option<Duration>) -> io::Result<()>
+where
+    F: Fn(&str) -> bool,
+{
+    // Lines are read on a separate thread, so waiting for them can time out.
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    let interactive = io::stdin().is_terminal();
+    loop {
+        if interactive {
+            eprint!("rgp> ");
+            io::stderr().flush()?;
+        }
+        let line = match idle_timeout {
+            Some(timeout) => receiver.recv_timeout(timeout),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        let line = match line {
+            Ok(line) => line?,
+            Err(RecvTimeoutError::Timeout) => {
+                drop(session);
+                if interactive {
+                    eprintln!();
+                }
+                eprintln!("Session idle, master password wiped");
+                return Ok(());
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                if interactive {
+                    eprintln!();
+                }
+                return Ok(());
+            }
+        };
+        match session.handle(&line) {
+            Ok(Step::Print(text)) => println!("{}", text),
+            Ok(Step::Continue) => {}
+            Ok(Step::Quit) => return Ok(()),
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_shell {
+    use super::*;
+    use crate::options::Scope;
+    use crate::sites::SiteStore;
+    use clap::Parser;
+
+    fn session() -> Session<fn(&str) -> bool> {
+        let generator = Generator::with_store("masterpassword".to_string(), SiteStore::default());
+        Session::new(generator, Cli::parse_from(["rgp"]), |_| false)
+    }
+
+    #[test]
+    fn generates_password_for_domain() {
+        assert_eq!(
+            Ok(Step::Print("jHMOHn7bRs".to_string())),
+            session().handle("https://www.example.com/foo")
+        );
+    }
+
+    #[test]
+    fn changes_settings_inline() {
+        let mut session = session();
+        assert_eq!(Ok(Step::Continue), session.handle(":length 4"));
+        assert_eq!(Ok(Step::Continue), session.handle(":rounds 1"));
+        assert_eq!(
+            Ok(Step::Print("xe4X".to_string())),
+            session.handle("example.com")
+        );
+        assert_eq!(Ok(Step::Continue), session.handle(":format pin"));
+        let Ok(Step::Print(show)) = session.handle(":show") else {
+            panic!("settings not shown");
+        };
+        for line in [
+            "length = 4",
+            "rounds = 1",
+            "format = pin",
+            "pin-length = 4",
+            "login = <unset>",
+            "secret = <unset>",
+        ] {
+            assert!(show.lines().any(|shown| shown == line), "{}", line);
+        }
+    }
+
+    #[test]
+    fn documents_every_override() {
+        for setting in SETTINGS
+            .iter()
+            .filter(|setting| setting.scope >= Scope::Line)
+        {
+            assert!(
+                HELP.contains(&format!("  :{} <", setting.key)),
+                "{}",
+                setting.key
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_commands() {
+        let mut session = session();
+        assert!(session.handle(":length 40").is_err());
+        assert!(session.handle(":salt 2").is_err());
+        assert!(session.handle(":show all").is_err());
+        assert!(session.handle(":frobnicate").is_err());
+    }
+
+    #[test]
+    fn rejects_unused_charset() {
+        let mut session = session();
+        assert_eq!(Ok(Step::Continue), session.handle(":charset 0123456789"));
+        assert!(session.handle("example.com").is_ok());
+        assert_eq!(Ok(Step::Continue), session.handle(":format passphrase"));
+        assert!(session.handle("example.com").is_err());
+        assert_eq!(Ok(Step::Continue), session.handle(":format password"));
+        assert_eq!(Ok(Step::Continue), session.handle(":scheme lesspass"));
+        assert!(session.handle("example.com").is_err());
+        assert_eq!(Ok(Step::Continue), session.handle(":scheme passwordmaker"));
+        assert!(session.handle("example.com").is_ok());
+    }
+
+    #[test]
+    fn quits() {
+        assert_eq!(Ok(Step::Quit), session().handle(":quit"));
+    }
+}
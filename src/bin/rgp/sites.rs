@@ -0,0 +1,419 @@
+//! Per-site profiles stored next to the configuration file.
+//!
+//! Profiles are plain TOML, except for site secrets which are kept in an
+//! encrypted section sealed with XChaCha20-Poly1305 under a key derived from
+//! the master password with Argon2id.
+
+use crate::cli::Cli;
+use crate::options::{self, SETTINGS, Scope};
+use crate::settings::config_dir;
+use argon2::{Argon2, Params, Version};
+use chacha20poly1305::{
+    Key, KeyInit, XChaCha20Poly1305, XNonce,
+    aead::{Aead, AeadCore, OsRng, Payload, rand_core::RngCore},
+};
+use clap_complete::CompletionCandidate;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+use zeroize::Zeroizing;
+
+const STORE_VERSION: u8 = 1;
+const SALT_LENGTH: usize = 16;
+const KEY_LENGTH: usize = 32;
+
+/// Site profiles and their encrypted secrets.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SiteStore {
+    #[serde(default)]
+    pub sites: BTreeMap<String, SiteProfile>,
+    pub encrypted: Option<EncryptedSection>,
+}
+
+/// Settings stored for a single site, overriding configured defaults.
+///
+/// Settings are keyed like in the configuration file, for the options with
+/// the site scope.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SiteProfile {
+    #[serde(flatten)]
+    pub settings: BTreeMap<String, toml::Value>,
+    /// The site has a secret in the encrypted section
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub secret: bool,
+}
+
+/// Secrets sealed with a key derived from the master password.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct EncryptedSection {
+    pub version: u8,
+    pub memory_cost: u32,
+    pub time_cost: u32,
+    pub parallelism: u32,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct Secrets {
+    #[serde(default)]
+    secrets: BTreeMap<String, String>,
+}
+
+/// Default location of the site store, next to the configuration file.
+pub fn default_sites_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("sites.toml"))
+}
+
+/// Stored domains as completion candidates, ignoring a missing or broken
+/// site store.
+pub fn site_candidates() -> Vec<CompletionCandidate> {
+    default_sites_path()
+        .and_then(|path| SiteStore::load(&path).ok())
+        .map(|store| {
+            store
+                .sites
+                .into_keys()
+                .map(CompletionCandidate::new)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl SiteProfile {
+    /// Apply the stored settings to the options that weren't given on the
+    /// command line.
+    pub fn apply<F>(&self, cli: &mut Cli, from_command_line: F) -> Result<(), SiteError>
+    where
+        F: Fn(&str) -> bool,
+    {
+        for (key, value) in &self.settings {
+            let setting =
+                options::find(key, Scope::Site).ok_or_else(|| SiteError::Unknown(key.clone()))?;
+            if from_command_line(setting.id) {
+                continue;
+            }
+            setting
+                .read_toml(value)
+                .and_then(|string| (setting.set)(cli, &string))
+                .ok_or_else(|| SiteError::Invalid(setting.key, value.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Store the options in `cli` for which `stored` returns true.
+    pub fn store<F>(&mut self, cli: &Cli, stored: F)
+    where
+        F: Fn(&str) -> bool,
+    {
+        for setting in SETTINGS {
+            if setting.scope == Scope::Site
+                && stored(setting.id)
+                && let Some(value) = (setting.get)(cli).and_then(|value| setting.to_toml(&value))
+            {
+                self.settings.insert(setting.key.to_string(), value);
+            }
+        }
+    }
+}
+
+impl fmt::Display for SiteProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut settings: Vec<String> = self
+            .settings
+            .iter()
+            .map(|(key, value)| match value.as_str() {
+                Some(value) => format!("{}={}", key, value),
+                None => format!("{}={}", key, value),
+            })
+            .collect();
+        if self.secret {
+            settings.push("secret".to_string());
+        }
+        write!(f, "{}", settings.join(" "))
+    }
+}
+
+impl SiteStore {
+    /// Read the site store, returning an empty store if the file is missing.
+    pub fn load(path: &Path) -> Result<Self, SiteError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents).map_err(SiteError::Parse),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(SiteError::Io(err)),
+        }
+    }
+
+    /// Write the site store, readable only by the current user.
+    pub fn save(&self, path: &Path) -> Result<(), SiteError> {
+        let contents = toml::to_string(self).map_err(SiteError::Serialize)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(SiteError::Io)?;
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(path)?;
+        // The mode only applies to new files, an existing store may have been
+        // created or changed with looser permissions.
+        #[cfg(unix)]
+        file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+        io::Write::write_all(&mut file, contents.as_bytes())?;
+        Ok(())
+    }
+
+    /// Decrypt the secrets of all sites with the master password.
+    pub fn secrets(&self, password: &str) -> Result<BTreeMap<String, String>, SiteError> {
+        let section = match &self.encrypted {
+            Some(section) => section,
+            None => return Ok(BTreeMap::new()),
+        };
+        if section.version != STORE_VERSION {
+            return Err(SiteError::UnsupportedVersion(section.version));
+        }
+        let salt = decode(&section.salt)?;
+        let nonce = decode(&section.nonce)?;
+        if nonce.len() != 24 {
+            return Err(SiteError::Corrupt);
+        }
+        let params = Params::new(
+            section.memory_cost,
+            section.time_cost,
+            section.parallelism,
+            Some(KEY_LENGTH),
+        )
+        .map_err(|_| SiteError::Corrupt)?;
+        let cipher = cipher(password, &salt, params)?;
+        let plaintext = cipher
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &decode(&section.ciphertext)?,
+                    aad: section.associated_data().as_bytes(),
+                },
+            )
+            .map_err(|_| SiteError::Decrypt)?;
+        let plaintext = String::from_utf8(plaintext).map_err(|_| SiteError::Corrupt)?;
+        let secrets: Secrets = toml::from_str(&plaintext).map_err(|_| SiteError::Corrupt)?;
+        Ok(secrets.secrets)
+    }
+
+    /// Encrypt the secrets of all sites with the master password, using a
+    /// fresh salt and nonce.
+    pub fn set_secrets(
+        &mut self,
+        password: &str,
+        secrets: BTreeMap<String, String>,
+    ) -> Result<(), SiteError> {
+        for (domain, profile) in self.sites.iter_mut() {
+            profile.secret = secrets.contains_key(domain);
+        }
+        if secrets.is_empty() {
+            self.encrypted = None;
+            return Ok(());
+        }
+        let params = Params::default();
+        let mut salt = [0u8; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let mut section = EncryptedSection {
+            version: STORE_VERSION,
+            memory_cost: params.m_cost(),
+            time_cost: params.t_cost(),
+            parallelism: params.p_cost(),
+            salt: base64::encode(salt),
+            nonce: base64::encode(nonce),
+            ciphertext: String::new(),
+        };
+        let plaintext = toml::to_string(&Secrets { secrets }).map_err(SiteError::Serialize)?;
+        let ciphertext = cipher(password, &salt, params)?
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext.as_bytes(),
+                    aad: section.associated_data().as_bytes(),
+                },
+            )
+            .map_err(|_| SiteError::Corrupt)?;
+        section.ciphertext = base64::encode(ciphertext);
+        self.encrypted = Some(section);
+        Ok(())
+    }
+}
+
+impl EncryptedSection {
+    /// Parameters bound to the ciphertext, so tampering with them is detected.
+    fn associated_data(&self) -> String {
+        format!(
+            "rgp-sites v{} m={} t={} p={} salt={}",
+            self.version, self.memory_cost, self.time_cost, self.parallelism, self.salt
+        )
+    }
+}
+
+fn cipher(password: &str, salt: &[u8], params: Params) -> Result<XChaCha20Poly1305, SiteError> {
+    let mut key = Zeroizing::new([0u8; KEY_LENGTH]);
+    Argon2::new(argon2::Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, key.as_mut())
+        .map_err(|_| SiteError::Corrupt)?;
+    Ok(XChaCha20Poly1305::new(Key::from_slice(key.as_ref())))
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, SiteError> {
+    base64::decode(value).map_err(|_| SiteError::Corrupt)
+}
+
+#[derive(Debug)]
+pub enum SiteError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+    Invalid(&'static str, String),
+    Unknown(String),
+    UnsupportedVersion(u8),
+    Corrupt,
+    Decrypt,
+}
+
+impl From<io::Error> for SiteError {
+    fn from(err: io::Error) -> Self {
+        SiteError::Io(err)
+    }
+}
+
+impl fmt::Display for SiteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SiteError::Io(err) => write!(f, "Couldn't access site store: {}", err),
+            SiteError::Parse(err) => write!(f, "Invalid site store: {}", err),
+            SiteError::Serialize(err) => write!(f, "Couldn't write site store: {}", err),
+            SiteError::Invalid(key, value) => {
+                write!(f, "Invalid value for {} in site store: {}", key, value)
+            }
+            SiteError::Unknown(key) => write!(f, "Unknown key {} in site store", key),
+            SiteError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported site store version: {}", version)
+            }
+            SiteError::Corrupt => write!(f, "Encrypted site secrets are corrupt"),
+            SiteError::Decrypt => write!(
+                f,
+                "Couldn't decrypt site secrets: wrong master password or tampered site store"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SiteError {}
+
+#[cfg(test)]
+mod test_site_store {
+    use super::*;
+    use clap::Parser;
+
+    fn store_with_secret() -> SiteStore {
+        let mut store = SiteStore::default();
+        store
+            .sites
+            .insert("example.com".to_string(), SiteProfile::default());
+        let secrets = BTreeMap::from([("example.com".to_string(), "secret".to_string())]);
+        store.set_secrets("masterpassword", secrets).unwrap();
+        store
+    }
+
+    #[test]
+    fn decrypts_secrets_with_master_password() {
+        let store = store_with_secret();
+        assert!(store.sites["example.com"].secret);
+        assert_eq!(
+            "secret",
+            store.secrets("masterpassword").unwrap()["example.com"]
+        );
+    }
+
+    #[test]
+    fn survives_round_trip_through_toml() {
+        let store = store_with_secret();
+        let store: SiteStore = toml::from_str(&toml::to_string(&store).unwrap()).unwrap();
+        assert_eq!(
+            "secret",
+            store.secrets("masterpassword").unwrap()["example.com"]
+        );
+    }
+
+    #[test]
+    fn applies_settings_not_given_on_command_line() {
+        let store: SiteStore = toml::from_str(
+            "[sites.\"example.com\"]\nlength = 16\nhash = \"sha512\"\nsecret = true\n",
+        )
+        .unwrap();
+        let profile = &store.sites["example.com"];
+        assert!(profile.secret);
+        assert_eq!("hash=sha512 length=16 secret", profile.to_string());
+        let mut cli = Cli::parse_from(["rgp", "--length", "12"]);
+        profile.apply(&mut cli, |id| id == "length").unwrap();
+        assert_eq!(Some(12), cli.length);
+        assert!(matches!(cli.hash, rustgenpass::HashAlgorithm::SHA512));
+    }
+
+    #[test]
+    fn stores_settings_given_on_command_line() {
+        let cli = Cli::parse_from(["rgp", "--length", "12", "--clip", "--login", "me"]);
+        let mut profile = SiteProfile::default();
+        profile.store(&cli, |id| ["length", "clip", "login"].contains(&id));
+        assert_eq!("length=12 login=me", profile.to_string());
+        assert_eq!(
+            "length = 12\nlogin = \"me\"\n",
+            toml::to_string(&profile).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_settings() {
+        let mut profile = SiteProfile::default();
+        profile
+            .settings
+            .insert("clip".to_string(), toml::Value::Boolean(true));
+        let mut cli = Cli::parse_from(["rgp"]);
+        assert!(matches!(
+            profile.apply(&mut cli, |_| false),
+            Err(SiteError::Unknown(key)) if key == "clip"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restricts_permissions_of_existing_store() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = std::env::temp_dir().join(format!("rgp-sites-{}.toml", std::process::id()));
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        store_with_secret().save(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(0o600, mode & 0o777);
+    }
+
+    #[test]
+    fn rejects_wrong_master_password() {
+        let store = store_with_secret();
+        assert!(matches!(store.secrets("wrong"), Err(SiteError::Decrypt)));
+    }
+
+    #[test]
+    fn detects_tampered_parameters() {
+        let mut store = store_with_secret();
+        store.encrypted.as_mut().unwrap().time_cost += 1;
+        assert!(matches!(
+            store.secrets("masterpassword"),
+            Err(SiteError::Decrypt)
+        ));
+    }
+}
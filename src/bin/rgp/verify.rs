@@ -0,0 +1,145 @@
+//! Searching the options an existing password was generated with.
+//!
+//! The length is given by the password itself, so the search covers the
+//! hash rounds, hashing algorithms, hostname isolation and whether the
+//! secret was used.
+
+use rustgenpass::{
+    GenerateConfig, HashAlgorithm, HostnameConfig, generate_with_config, get_hostname_with_config,
+};
+use std::{error::Error, fmt};
+
+/// Options reproducing the password.
+#[derive(Debug, PartialEq)]
+pub struct Match {
+    pub domain: String,
+    pub keep_subdomains: bool,
+    pub passthrough: bool,
+    pub length: usize,
+    pub rounds: u8,
+    pub hash: String,
+    pub secret: bool,
+}
+
+impl fmt::Display for Match {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "domain={} length={} rounds={} hash={} keep-subdomains={} passthrough={} secret={}",
+            self.domain,
+            self.length,
+            self.rounds,
+            self.hash,
+            self.keep_subdomains,
+            self.passthrough,
+            self.secret
+        )
+    }
+}
+
+/// Find all combinations of options up to `max_rounds` hash rounds that
+/// generate `expected` from the master password and domain / URL.
+pub fn search(
+    password: &str,
+    input: &str,
+    expected: &str,
+    secret: Option<&str>,
+    max_rounds: u8,
+) -> Result<Vec<Match>, Box<dyn Error>> {
+    let length = expected.len();
+    if !(4..=24).contains(&length) {
+        return Err(format!("Password length must be between 4 and 24, not {}", length).into());
+    }
+
+    // Different hostname options often isolate the same domain, which only
+    // needs to be searched once.
+    let mut domains: Vec<(String, bool, bool)> = Vec::new();
+    for (keep_subdomains, passthrough) in [(false, false), (true, false), (false, true)] {
+        let config = HostnameConfig {
+            keep_subdomains,
+            passthrough,
+        };
+        let domain = get_hostname_with_config(input, config)?;
+        if !domains.iter().any(|(known, _, _)| *known == domain) {
+            domains.push((domain, keep_subdomains, passthrough));
+        }
+    }
+
+    let mut secrets = vec![None];
+    if let Some(secret) = secret {
+        secrets.push(Some(secret.to_string()));
+    }
+
+    let mut matches = Vec::new();
+    for (domain, keep_subdomains, passthrough) in &domains {
+        for hash_algorithm in [HashAlgorithm::MD5, HashAlgorithm::SHA512] {
+            for secret in &secrets {
+                for rounds in 1..=max_rounds {
+                    let config = GenerateConfig {
+                        secret: secret.clone(),
+                        length,
+                        hash_rounds: rounds,
+                        hash_algorithm: hash_algorithm.clone(),
+                    };
+                    if generate_with_config(password, domain.as_str(), config) == expected {
+                        matches.push(Match {
+                            domain: domain.clone(),
+                            keep_subdomains: *keep_subdomains,
+                            passthrough: *passthrough,
+                            length,
+                            rounds,
+                            hash: hash_algorithm.to_string(),
+                            secret: secret.is_some(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod test_search {
+    use super::*;
+
+    #[test]
+    fn finds_default_options() {
+        let matches = search(
+            "masterpassword",
+            "https://www.example.com/foo/bar.html",
+            "jHMOHn7bRs",
+            None,
+            20,
+        )
+        .unwrap();
+        // Rounds are a minimum, so fewer rounds may end on the same password
+        assert!(matches.iter().any(|found| found.domain == "example.com"
+            && found.rounds == 10
+            && found.hash == "md5"
+            && !found.secret));
+        assert!(matches.iter().all(|found| found.hash == "md5"));
+    }
+
+    #[test]
+    fn finds_secret_and_sha512() {
+        let matches = search(
+            "masterpassword",
+            "example.com",
+            "wqSjM4Vrmz",
+            Some("secret"),
+            20,
+        )
+        .unwrap();
+        assert!(
+            matches
+                .iter()
+                .any(|found| found.rounds == 10 && found.hash == "sha512" && found.secret)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_length() {
+        assert!(search("masterpassword", "example.com", "abc", None, 20).is_err());
+    }
+}
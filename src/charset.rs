@@ -0,0 +1,193 @@
+//! Passwords of a custom character set, for legacy systems accepting only
+//! some characters.
+//!
+//! The final hash is generated like the SuperGenPass password, with the same
+//! rounds and password policy. Characters are taken from the bytes of its
+//! SHA-512 digest, skipping bytes that would make some characters more likely
+//! than others, and the digest is hashed again when it runs out of bytes.
+//!
+//! The password policy still applies to the character classes in the set: the
+//! password starts with a lowercase letter and contains an uppercase letter
+//! and a digit, as far as the set has any. Passwords not satisfying it are
+//! skipped for the next one.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustgenpass::GenerateConfig;
+//! use rustgenpass::charset::generate;
+//! let generated = generate("masterpassword", "example.com", GenerateConfig::default(), "ABCDEFGHJKLMNPQRSTUVWXYZ23456789");
+//! assert_eq!("38JJZYHBHY", generated.unwrap().password);
+//! ```
+
+use crate::{GenerateConfig, GeneratedPassword, final_hash};
+use sha2::{Digest, Sha512};
+use std::fmt;
+
+/// Most distinct characters in a set, one per byte value.
+pub const MAX_CHARACTERS: usize = 256;
+
+/// Generate a password of the characters from the final SuperGenPass hash.
+///
+/// # Arguments
+///
+/// * `password` - Master password to generate password from
+/// * `domain` - Domain to generate password for
+/// * `config` - Secret, length, rounds and hash algorithm
+/// * `characters` - Characters of the password, repeated ones count once
+pub fn generate(
+    password: &str,
+    domain: &str,
+    config: GenerateConfig,
+    characters: &str,
+) -> Result<GeneratedPassword, CharsetError> {
+    let characters = distinct(characters);
+    if characters.len() < 2 {
+        return Err(CharsetError::InsufficientCharacters);
+    }
+    if characters.len() > MAX_CHARACTERS {
+        return Err(CharsetError::TooManyCharacters(characters.len()));
+    }
+    let (hash, rounds) = final_hash(password, domain, &config);
+    let mut bytes = Bytes::new(hash.as_bytes());
+    // Bytes from the limit up would pick the first characters more often.
+    let limit = MAX_CHARACTERS - MAX_CHARACTERS % characters.len();
+    let classes = Classes::of(&characters);
+    loop {
+        let generated: Vec<char> = (0..config.length)
+            .map(|_| {
+                loop {
+                    let byte = usize::from(bytes.next());
+                    if byte < limit {
+                        break characters[byte % characters.len()];
+                    }
+                }
+            })
+            .collect();
+        if classes.satisfied_by(&generated) {
+            return Ok(GeneratedPassword {
+                password: generated.into_iter().collect(),
+                rounds,
+            });
+        }
+    }
+}
+
+/// Entropy in bits of the passwords of `length` characters from the set
+/// satisfying the password policy.
+///
+/// # Examples
+///
+/// ```
+/// use rustgenpass::charset::entropy;
+/// assert_eq!(27.0, entropy("0123456789abcdef", 7).round());
+/// ```
+pub fn entropy(characters: &str, length: usize) -> f64 {
+    let characters = distinct(characters);
+    let classes = Classes::of(&characters);
+    // Passwords containing the required uppercase letters and digits, by
+    // inclusion–exclusion over the passwords missing them.
+    let containing = |length: usize| {
+        let missing = |without: usize| ((characters.len() - without) as f64).powi(length as i32);
+        let mut count = missing(0);
+        if classes.uppercase > 0 {
+            count -= missing(classes.uppercase);
+        }
+        if classes.digits > 0 {
+            count -= missing(classes.digits);
+        }
+        if classes.uppercase > 0 && classes.digits > 0 {
+            count += missing(classes.uppercase + classes.digits);
+        }
+        count
+    };
+    let passwords = if classes.lowercase > 0 {
+        classes.lowercase as f64 * containing(length - 1)
+    } else {
+        containing(length)
+    };
+    passwords.log2()
+}
+
+fn distinct(characters: &str) -> Vec<char> {
+    let mut distinct = Vec::new();
+    for character in characters.chars() {
+        if !distinct.contains(&character) {
+            distinct.push(character);
+        }
+    }
+    distinct
+}
+
+/// Bytes of a digest, hashed again when they run out.
+struct Bytes {
+    digest: [u8; 64],
+    position: usize,
+}
+
+impl Bytes {
+    fn new(hash: &[u8]) -> Self {
+        Self {
+            digest: Sha512::digest(hash).into(),
+            position: 0,
+        }
+    }
+
+    fn next(&mut self) -> u8 {
+        if self.position == self.digest.len() {
+            self.digest = Sha512::digest(self.digest).into();
+            self.position = 0;
+        }
+        self.position += 1;
+        self.digest[self.position - 1]
+    }
+}
+
+/// Number of characters of the classes in the password policy, of which
+/// empty classes aren't required.
+struct Classes {
+    lowercase: usize,
+    uppercase: usize,
+    digits: usize,
+}
+
+impl Classes {
+    fn of(characters: &[char]) -> Self {
+        let count = |class: fn(&char) -> bool| characters.iter().filter(|c| class(c)).count();
+        Self {
+            lowercase: count(char::is_ascii_lowercase),
+            uppercase: count(char::is_ascii_uppercase),
+            digits: count(char::is_ascii_digit),
+        }
+    }
+
+    fn satisfied_by(&self, password: &[char]) -> bool {
+        (self.lowercase == 0 || password[0].is_ascii_lowercase())
+            && (self.uppercase == 0 || password.iter().any(char::is_ascii_uppercase))
+            && (self.digits == 0 || password.iter().any(char::is_ascii_digit))
+    }
+}
+
+/// Character set no password can be generated from.
+#[derive(Debug, PartialEq)]
+pub enum CharsetError {
+    InsufficientCharacters,
+    TooManyCharacters(usize),
+}
+
+impl fmt::Display for CharsetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CharsetError::InsufficientCharacters => {
+                write!(f, "The character set requires at least 2 characters")
+            }
+            CharsetError::TooManyCharacters(count) => write!(
+                f,
+                "Too many characters in the character set: {}, max: {}",
+                count, MAX_CHARACTERS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CharsetError {}
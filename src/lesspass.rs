@@ -0,0 +1,139 @@
+//! Passwords compatible with [LessPass](https://www.lesspass.com/), using
+//! version 2 of its algorithm.
+//!
+//! The entropy is derived from the site, the login and the counter with
+//! PBKDF2-SHA256, and rendered as a password with at least one character of
+//! each of the enabled character sets.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustgenpass::lesspass::{generate, Profile};
+//! let password = generate("password", "example.org", "contact@example.org", &Profile::default());
+//! assert_eq!("WHLpUL)e00[iHR+w", password.unwrap());
+//! ```
+
+use crate::big_number::div_rem;
+use sha2::Sha256;
+use std::fmt;
+
+/// Iterations of PBKDF2.
+const ITERATIONS: u32 = 100_000;
+
+/// Shortest password LessPass generates.
+pub const MIN_LENGTH: usize = 5;
+
+/// Longest password LessPass generates.
+pub const MAX_LENGTH: usize = 35;
+
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// Settings of a site in LessPass.
+#[derive(Clone, Debug)]
+pub struct Profile {
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+    pub length: usize,
+    pub counter: u32,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            lowercase: true,
+            uppercase: true,
+            digits: true,
+            symbols: true,
+            length: 16,
+            counter: 1,
+        }
+    }
+}
+
+/// Generate a LessPass password.
+///
+/// # Arguments
+///
+/// * `password` - Master password
+/// * `site` - Site name, usually the domain
+/// * `login` - Login at the site, can be empty
+/// * `profile` - Character sets, length and counter of the password
+pub fn generate(
+    password: &str,
+    site: &str,
+    login: &str,
+    profile: &Profile,
+) -> Result<String, LessPassError> {
+    if !(MIN_LENGTH..=MAX_LENGTH).contains(&profile.length) {
+        return Err(LessPassError::InvalidLength(profile.length));
+    }
+    let rules: Vec<&str> = [
+        (profile.lowercase, LOWERCASE),
+        (profile.uppercase, UPPERCASE),
+        (profile.digits, DIGITS),
+        (profile.symbols, SYMBOLS),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, characters)| enabled.then_some(characters))
+    .collect();
+    if rules.is_empty() {
+        return Err(LessPassError::NoCharacterSets);
+    }
+
+    let salt = format!("{}{}{:x}", site, login, profile.counter);
+    let mut entropy = [0; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(
+        password.as_bytes(),
+        salt.as_bytes(),
+        ITERATIONS,
+        &mut entropy,
+    );
+
+    // Characters from all sets, followed by one character of each set
+    // inserted at pseudo-random positions.
+    let characters: Vec<char> = rules.concat().chars().collect();
+    let mut generated: Vec<char> = (0..profile.length - rules.len())
+        .map(|_| characters[div_rem(&mut entropy, characters.len())])
+        .collect();
+    let required: Vec<char> = rules
+        .iter()
+        .map(|rule| {
+            let characters: Vec<char> = rule.chars().collect();
+            characters[div_rem(&mut entropy, characters.len())]
+        })
+        .collect();
+    for character in required {
+        let position = div_rem(&mut entropy, generated.len());
+        generated.insert(position, character);
+    }
+    Ok(generated.into_iter().collect())
+}
+
+/// Profile LessPass can't generate a password for.
+#[derive(Debug, PartialEq)]
+pub enum LessPassError {
+    InvalidLength(usize),
+    NoCharacterSets,
+}
+
+impl fmt::Display for LessPassError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LessPassError::InvalidLength(length) => write!(
+                f,
+                "Invalid length for LessPass: {}, min: {}, max: {}",
+                length, MIN_LENGTH, MAX_LENGTH
+            ),
+            LessPassError::NoCharacterSets => {
+                write!(f, "LessPass requires at least one character set")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LessPassError {}
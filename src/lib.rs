@@ -3,6 +3,12 @@
 //! Hash a master password into unique, complex passwords specific for each
 //! website.
 //!
+//! # Features
+//!
+//! * `cli` - Command line integration used by the `rgp` binary, enabled by
+//!   default. Disable default features to use the library without
+//!   depending on clap and the other CLI crates.
+//!
 //! # Examples
 //!
 //! ## Use default to parse domain from URL and strip subdomains
@@ -76,7 +82,18 @@
 
 #[macro_use]
 extern crate lazy_static;
-use clap::Parser;
+
+pub mod answer;
+mod big_number;
+pub mod charset;
+pub mod lesspass;
+pub mod passphrase;
+pub mod passwordmaker;
+pub mod pin;
+pub mod pwdhash;
+pub mod scheme;
+pub mod spectre;
+
 use md5::Md5;
 use regex::Regex;
 use sha2::{Digest, Sha512};
@@ -160,25 +177,175 @@ pub fn generate_with_config<S: Into<String>>(
     domain: S,
     config: GenerateConfig,
 ) -> String {
+    generate_with_details(password, domain, config).password
+}
+
+/// Generate a hashed password with given options, reporting how it was
+/// generated.
+///
+/// The number of rounds performed can be higher than `hash_rounds`, as
+/// hashing continues until the password satisfies the password policy.
+///
+/// # Examples
+///
+/// ```
+/// use rustgenpass::{generate_with_details, GenerateConfig};
+/// let generated = generate_with_details("masterpassword", "example.com", GenerateConfig::default());
+/// assert_eq!("jHMOHn7bRs", generated.password);
+/// assert_eq!(10, generated.rounds);
+/// ```
+pub fn generate_with_details<S: Into<String>>(
+    password: S,
+    domain: S,
+    config: GenerateConfig,
+) -> GeneratedPassword {
+    let (hash, rounds) = final_hash(&password.into(), &domain.into(), &config);
+    GeneratedPassword {
+        password: hash[..config.length].to_string(),
+        rounds,
+    }
+}
+
+/// Hash the master password, secret and domain like SuperGenPass, returning
+/// the final hash and the number of rounds performed.
+pub(crate) fn final_hash(password: &str, domain: &str, config: &GenerateConfig) -> (String, u32) {
     let mut hash: String = format!(
         "{}{}:{}",
-        password.into(),
-        config.secret.unwrap_or_else(|| "".to_string()),
-        domain.into()
+        password,
+        config.secret.as_deref().unwrap_or_default(),
+        domain
     );
 
     // Hash the input for the requested number of rounds, then continue hashing
     // until the password policy is satisfied.
-    let mut i = 0;
-    while i < config.hash_rounds || !validate_password(&hash[..config.length]) {
+    let mut i: u32 = 0;
+    while i < u32::from(config.hash_rounds) || !validate_password(&hash[..config.length]) {
         hash = match config.hash_algorithm {
             HashAlgorithm::MD5 => base64_md5(hash),
             HashAlgorithm::SHA512 => base64_sha512(hash),
         };
         i += 1;
     }
+    (hash, i)
+}
+
+/// Generate a short fingerprint of a master password.
+///
+/// Showing the fingerprint when the master password is entered lets the user
+/// spot typos, without revealing the password itself.
+///
+/// # Arguments
+///
+/// * `password` - Master password to fingerprint
+///
+/// # Examples
+///
+/// ```
+/// use rustgenpass::fingerprint;
+/// assert_eq!(8, fingerprint("masterpassword").len());
+/// ```
+pub fn fingerprint<S: Into<String>>(password: S) -> String {
+    let digest = Sha512::digest(format!("rustgenpass-fingerprint:{}", password.into()));
+    digest[..3]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Estimate the entropy of passwords generated with given options.
+///
+/// Passwords are assumed to come from an ideal hash, so the estimate is an
+/// upper bound: the generated passwords can never be stronger than the
+/// master password and secret they are generated from.
+///
+/// The estimate accounts for `+` and `/` being replaced by `9` and `8`, for
+/// the padding of MD5 hashes always ending in `AA`, and for the passwords
+/// rejected by the password policy.
+///
+/// # Arguments
+///
+/// * `config` - Options to generate passwords with
+///
+/// # Examples
+///
+/// ```
+/// use rustgenpass::{entropy, GenerateConfig};
+/// let entropy = entropy(&GenerateConfig::default());
+/// assert_eq!(58, entropy.bits.round() as u32);
+/// assert_eq!(59, entropy.unconstrained_bits.round() as u32);
+/// ```
+pub fn entropy(config: &GenerateConfig) -> PasswordEntropy {
+    let unconstrained_bits = (0..config.length)
+        .map(|index| {
+            position_classes(&config.hash_algorithm, index)
+                .iter()
+                .map(|(_, surprisal)| surprisal)
+                .sum::<f64>()
+        })
+        .sum();
+
+    // Probability and probability weighted surprisal of the prefixes, by
+    // whether they contain an uppercase letter and a numeral.
+    let mut states = [[(0.0, 0.0); 2]; 2];
+    states[0][0] = (1.0, 0.0);
+    for index in 0..config.length {
+        let classes = position_classes(&config.hash_algorithm, index);
+        let mut next = [[(0.0, 0.0); 2]; 2];
+        for (uppercase, row) in states.iter().enumerate() {
+            for (numeral, (probability, weighted)) in row.iter().enumerate() {
+                for (class, (class_probability, class_weighted)) in classes.iter().enumerate() {
+                    // The policy requires a lowercase letter first.
+                    if index == 0 && class != LOWERCASE {
+                        continue;
+                    }
+                    let state = &mut next[uppercase | usize::from(class == UPPERCASE)]
+                        [numeral | usize::from(class == NUMERAL)];
+                    state.0 += probability * class_probability;
+                    state.1 += weighted * class_probability + probability * class_weighted;
+                }
+            }
+        }
+        states = next;
+    }
+
+    // Entropy of the passwords conditioned on satisfying the policy.
+    let (acceptance_rate, weighted) = states[1][1];
+    let bits = if acceptance_rate > 0.0 {
+        weighted / acceptance_rate + acceptance_rate.log2()
+    } else {
+        0.0
+    };
 
-    hash[..config.length].to_string()
+    PasswordEntropy {
+        bits,
+        unconstrained_bits,
+        acceptance_rate,
+    }
+}
+
+const LOWERCASE: usize = 0;
+const UPPERCASE: usize = 1;
+const NUMERAL: usize = 2;
+
+/// Probability and probability weighted surprisal of lowercase letters,
+/// uppercase letters and numerals at a position of a hash.
+fn position_classes(algorithm: &HashAlgorithm, index: usize) -> [(f64, f64); 3] {
+    let bits = |probability: f64| -probability * probability.log2();
+    match (algorithm, index) {
+        // 128 bits of MD5 fill 21 characters, leaving two bits for the 22nd
+        // character, which is one of `A`, `Q`, `g` and `w`.
+        (HashAlgorithm::MD5, 21) => [(0.5, 2.0 * bits(0.25)), (0.5, 2.0 * bits(0.25)), (0.0, 0.0)],
+        // Padding replaced with `A`.
+        (HashAlgorithm::MD5, 22..) => [(0.0, 0.0), (1.0, 0.0), (0.0, 0.0)],
+        // Letters are equally likely, while `8` and `9` are twice as likely
+        // as the other numerals, as they also replace `/` and `+`.
+        _ => [
+            (26.0 / 64.0, 26.0 * bits(1.0 / 64.0)),
+            (26.0 / 64.0, 26.0 * bits(1.0 / 64.0)),
+            (12.0 / 64.0, 8.0 * bits(1.0 / 64.0) + 2.0 * bits(2.0 / 64.0)),
+        ],
+    }
 }
 
 /// Isolate the domain name of a URL with default config.
@@ -312,6 +479,32 @@ impl fmt::Display for RustgenpassError {
 
 impl std::error::Error for RustgenpassError {}
 
+/// Generated password and the number of hash rounds performed.
+#[derive(Debug)]
+pub struct GeneratedPassword {
+    pub password: String,
+    pub rounds: u32,
+}
+
+/// Estimated entropy of generated passwords, see [`entropy`].
+#[derive(Debug)]
+pub struct PasswordEntropy {
+    /// Entropy in bits of the passwords satisfying the password policy.
+    pub bits: f64,
+    /// Entropy in bits of the hashes before applying the password policy.
+    pub unconstrained_bits: f64,
+    /// Fraction of hashes satisfying the password policy.
+    pub acceptance_rate: f64,
+}
+
+impl PasswordEntropy {
+    /// Average number of rounds hashed beyond `hash_rounds` until the
+    /// password satisfies the password policy.
+    pub fn extra_rounds(&self) -> f64 {
+        (1.0 - self.acceptance_rate) / self.acceptance_rate
+    }
+}
+
 #[derive(Debug)]
 pub struct GenerateConfig {
     pub secret: Option<String>,
@@ -331,82 +524,30 @@ impl Default for GenerateConfig {
     }
 }
 
-impl From<Cli> for GenerateConfig {
-    fn from(cli: Cli) -> Self {
-        Self {
-            secret: cli.secret,
-            length: cli.length as usize,
-            hash_rounds: cli.rounds,
-            hash_algorithm: cli.hash,
-        }
-    }
-}
-
 #[derive(Default, Debug)]
 pub struct HostnameConfig {
     pub passthrough: bool,
     pub keep_subdomains: bool,
 }
 
-impl From<Cli> for HostnameConfig {
-    fn from(cli: Cli) -> Self {
-        Self {
-            passthrough: cli.passthrough,
-            keep_subdomains: cli.keep_subdomains,
-        }
-    }
-}
-
-#[derive(Clone, Debug, clap::ValueEnum)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 /// Supported hashing algorithms
 pub enum HashAlgorithm {
+    #[default]
     MD5,
     SHA512,
 }
 
-impl Default for HashAlgorithm {
-    fn default() -> Self {
-        HashAlgorithm::MD5
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HashAlgorithm::MD5 => write!(f, "md5"),
+            HashAlgorithm::SHA512 => write!(f, "sha512"),
+        }
     }
 }
 
-#[derive(Parser, Clone, Debug)]
-#[clap(author, version, about, long_about = None)]
-/// Options parsed from command line used by the binary
-pub struct Cli {
-    /// Master password, if not given, reads from stdin
-    #[clap(short, long, value_parser)]
-    pub password: Option<String>,
-
-    /// Secret added to the master password
-    #[clap(short, long, value_parser)]
-    pub secret: Option<String>,
-
-    /// Domain / URL to generate password for
-    #[clap(short, long, value_parser)]
-    pub domain: Option<String>,
-
-    /// Length of generated password, min: 4, max: 24
-    #[clap(short, long, default_value_t = 10, value_parser = clap::value_parser!(u8).range(4..=24))]
-    pub length: u8,
-
-    /// Number of hash rounds
-    #[clap(short, long, default_value_t = 10)]
-    pub rounds: u8,
-
-    /// Don't remove subdomains from domain
-    #[clap(short, long, action)]
-    pub keep_subdomains: bool,
-
-    /// Passthrough domain unmodified to hash function
-    #[clap(short = 'P', long, action)]
-    pub passthrough: bool,
-
-    /// Hashing method to use
-    #[clap(short = 'H', long, value_enum, default_value_t = HashAlgorithm::MD5)]
-    pub hash: HashAlgorithm,
-}
-
 #[cfg(test)]
 mod test_validate_password {
     use super::*;
@@ -418,16 +559,16 @@ mod test_validate_password {
 
     #[test]
     fn requires_an_uppercase_letter() {
-        assert_eq!(false, validate_password("a"));
+        assert!(!validate_password("a"));
     }
 
     #[test]
     fn requires_password_to_start_with_lowercase_letter() {
-        assert_eq!(false, validate_password("A"));
+        assert!(!validate_password("A"));
     }
 
     #[test]
     fn requires_a_number() {
-        assert_eq!(false, validate_password("aA"));
+        assert!(!validate_password("aA"));
     }
 }
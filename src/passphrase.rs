@@ -0,0 +1,167 @@
+//! Passphrases of words from the
+//! [EFF large wordlist](https://www.eff.org/dice), for systems accepting long
+//! passphrases.
+//!
+//! The final hash is generated like the SuperGenPass password, with the same
+//! rounds and password policy, and its SHA-512 digest picks the words and the
+//! optional digit. The wordlist by the Electronic Frontier Foundation is
+//! licensed under CC BY 3.0 US.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustgenpass::GenerateConfig;
+//! use rustgenpass::passphrase::{generate, PassphraseConfig};
+//! let generated = generate("masterpassword", "example.com", GenerateConfig::default(), &PassphraseConfig::default());
+//! assert_eq!("freebie-destiny-saga-strung-triage-runner", generated.unwrap().password);
+//! ```
+
+use crate::big_number::div_rem;
+use crate::{GenerateConfig, GeneratedPassword, final_hash};
+use sha2::{Digest, Sha512};
+use std::fmt;
+
+lazy_static! {
+    static ref WORDLIST: Vec<&'static str> =
+        include_str!("eff_large_wordlist.txt").lines().collect();
+}
+
+/// Fewest words in a passphrase.
+pub const MIN_WORDS: usize = 3;
+
+/// Most words in a passphrase.
+pub const MAX_WORDS: usize = 20;
+
+/// Words, separator, capitalization and digit of a passphrase.
+#[derive(Clone, Debug)]
+pub struct PassphraseConfig {
+    pub words: usize,
+    pub separator: String,
+    pub capitalization: Capitalization,
+    pub digit: bool,
+}
+
+impl Default for PassphraseConfig {
+    fn default() -> Self {
+        Self {
+            words: 6,
+            separator: "-".to_string(),
+            capitalization: Capitalization::default(),
+            digit: false,
+        }
+    }
+}
+
+/// Generate a passphrase from the final SuperGenPass hash.
+///
+/// # Arguments
+///
+/// * `password` - Master password to generate passphrase from
+/// * `domain` - Domain to generate passphrase for
+/// * `config` - Secret, rounds and hash algorithm, and the length the
+///   password policy is checked for
+/// * `passphrase` - Words, separator, capitalization and digit
+pub fn generate(
+    password: &str,
+    domain: &str,
+    config: GenerateConfig,
+    passphrase: &PassphraseConfig,
+) -> Result<GeneratedPassword, PassphraseError> {
+    if !(MIN_WORDS..=MAX_WORDS).contains(&passphrase.words) {
+        return Err(PassphraseError::InvalidWordCount(passphrase.words));
+    }
+    let (hash, rounds) = final_hash(password, domain, &config);
+    let mut entropy: [u8; 64] = Sha512::digest(hash).into();
+    let mut words: Vec<String> = (0..passphrase.words)
+        .map(|_| {
+            let word = WORDLIST[div_rem(&mut entropy, WORDLIST.len())];
+            passphrase.capitalization.apply(word)
+        })
+        .collect();
+    if passphrase.digit {
+        let digit = div_rem(&mut entropy, 10);
+        let position = div_rem(&mut entropy, words.len());
+        words[position].push_str(&digit.to_string());
+    }
+    Ok(GeneratedPassword {
+        password: words.join(&passphrase.separator),
+        rounds,
+    })
+}
+
+/// Entropy in bits of the passphrases generated with the configuration.
+///
+/// # Examples
+///
+/// ```
+/// use rustgenpass::passphrase::{entropy, PassphraseConfig};
+/// assert_eq!(77.5, (entropy(&PassphraseConfig::default()) * 10.0).round() / 10.0);
+/// ```
+pub fn entropy(passphrase: &PassphraseConfig) -> f64 {
+    let words = passphrase.words as f64;
+    let digit = if passphrase.digit {
+        (10.0 * words).log2()
+    } else {
+        0.0
+    };
+    words * (WORDLIST.len() as f64).log2() + digit
+}
+
+/// Capitalization of the words of a passphrase.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Capitalization {
+    /// All lowercase
+    #[default]
+    Lower,
+    /// First letter of each word uppercase
+    Title,
+    /// All uppercase
+    Upper,
+}
+
+impl Capitalization {
+    fn apply(&self, word: &str) -> String {
+        match self {
+            Capitalization::Lower => word.to_string(),
+            Capitalization::Title => {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+            Capitalization::Upper => word.to_uppercase(),
+        }
+    }
+}
+
+impl fmt::Display for Capitalization {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Capitalization::Lower => write!(f, "lower"),
+            Capitalization::Title => write!(f, "title"),
+            Capitalization::Upper => write!(f, "upper"),
+        }
+    }
+}
+
+/// Configuration no passphrase can be generated for.
+#[derive(Debug, PartialEq)]
+pub enum PassphraseError {
+    InvalidWordCount(usize),
+}
+
+impl fmt::Display for PassphraseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PassphraseError::InvalidWordCount(words) => write!(
+                f,
+                "Invalid number of words: {}, min: {}, max: {}",
+                words, MIN_WORDS, MAX_WORDS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PassphraseError {}
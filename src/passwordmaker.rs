@@ -0,0 +1,441 @@
+//! Passwords compatible with [PasswordMaker](https://passwordmaker.org/),
+//! as generated by PasswordMaker Pro.
+//!
+//! The text to use is taken from the parts of the URL selected with
+//! [`UrlComponents`], followed by the username and the modifier of the
+//! profile. It's hashed together with the master password, and the hash is
+//! rendered in the characters of the profile. Longer passwords are made of
+//! several hashes, with the number of the hash appended to the master
+//! password.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustgenpass::passwordmaker::{generate, Profile};
+//! let password = generate("masterpassword", "https://www.example.com/login", &Profile::default());
+//! assert_eq!("ZAy&DE<X", password.unwrap());
+//! ```
+
+use crate::HostnameConfig;
+use crate::big_number::div_rem;
+use hmac::{Mac, SimpleHmac, digest::core_api::BlockSizeUser};
+use md4::Md4;
+use md5::Md5;
+use ripemd::Ripemd160;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// Characters of the default profile of PasswordMaker.
+pub const DEFAULT_CHARACTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789`~!@#$%^&*()_-+={}|[]\\:\";'<>?,./";
+
+/// Settings of a profile in PasswordMaker.
+#[derive(Clone, Debug)]
+pub struct Profile {
+    pub algorithm: Algorithm,
+    pub leet: LeetMode,
+    /// Level of l33t from 1 to 9, the higher the more characters replaced
+    pub leet_level: u8,
+    pub characters: String,
+    pub username: String,
+    pub modifier: String,
+    pub length: usize,
+    pub prefix: String,
+    pub suffix: String,
+    pub url_components: UrlComponents,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            algorithm: Algorithm::default(),
+            leet: LeetMode::default(),
+            leet_level: 1,
+            characters: DEFAULT_CHARACTERS.to_string(),
+            username: String::new(),
+            modifier: String::new(),
+            length: 8,
+            prefix: String::new(),
+            suffix: String::new(),
+            url_components: UrlComponents::default(),
+        }
+    }
+}
+
+/// Generate a PasswordMaker password for a URL.
+///
+/// # Arguments
+///
+/// * `password` - Master password
+/// * `url` - URL to take the text to use from
+/// * `profile` - Settings of the PasswordMaker profile
+pub fn generate(
+    password: &str,
+    url: &str,
+    profile: &Profile,
+) -> Result<String, PasswordMakerError> {
+    generate_with_text(password, &profile.url_components.text_to_use(url), profile)
+}
+
+/// Generate a PasswordMaker password for the text to use, bypassing the URL
+/// components of the profile.
+///
+/// # Examples
+///
+/// ```
+/// use rustgenpass::passwordmaker::{generate_with_text, Profile};
+/// let password = generate_with_text("masterpassword", "example.com", &Profile::default());
+/// assert_eq!("ZAy&DE<X", password.unwrap());
+/// ```
+pub fn generate_with_text(
+    password: &str,
+    text: &str,
+    profile: &Profile,
+) -> Result<String, PasswordMakerError> {
+    let characters: Vec<char> = profile.characters.chars().collect();
+    if characters.len() < 2 {
+        return Err(PasswordMakerError::InsufficientCharacters);
+    }
+    if !(1..=9).contains(&profile.leet_level) {
+        return Err(PasswordMakerError::InvalidLeetLevel(profile.leet_level));
+    }
+    if text.is_empty() {
+        return Err(PasswordMakerError::EmptyText);
+    }
+
+    let data = format!("{}{}{}", text, profile.username, profile.modifier);
+    let prefix: Vec<char> = profile.prefix.chars().collect();
+    let suffix: Vec<char> = profile.suffix.chars().collect();
+    let needed = profile
+        .length
+        .saturating_sub(prefix.len())
+        .saturating_sub(suffix.len());
+    let mut generated = Vec::new();
+    let mut part = 0;
+    while generated.len() < needed {
+        let key = match part {
+            0 => password.to_string(),
+            _ => format!("{}\n{}", password, part),
+        };
+        let hashed = password_part(&key, &data, profile, &characters);
+        // l33t after hashing is applied to each part on its own.
+        let hashed = match profile.leet {
+            LeetMode::AfterHashing | LeetMode::Both => leetify(&hashed, profile.leet_level),
+            _ => hashed,
+        };
+        generated.extend(hashed.chars());
+        part += 1;
+    }
+    Ok(prefix
+        .iter()
+        .chain(&generated)
+        .take(profile.length.saturating_sub(suffix.len()))
+        .chain(&suffix)
+        .take(profile.length)
+        .collect())
+}
+
+/// Render one hash of the master password and the data.
+fn password_part(key: &str, data: &str, profile: &Profile, characters: &[char]) -> String {
+    let leet = |text: &str| match profile.leet {
+        LeetMode::BeforeHashing | LeetMode::Both => leetify(text, profile.leet_level),
+        _ => text.to_string(),
+    };
+    let algorithm = &profile.algorithm;
+    let (key, message) = if algorithm.is_hmac() {
+        (leet(key), leet(data))
+    } else {
+        (String::new(), leet(&format!("{}{}", key, data)))
+    };
+
+    if matches!(algorithm, Algorithm::Md5V6 | Algorithm::HmacMd5V6) {
+        // Version 0.6 hashed the low byte of each UTF-16 code unit, and
+        // always rendered the hash as hexadecimal.
+        let low_bytes =
+            |text: &str| -> Vec<u8> { text.encode_utf16().map(|unit| unit as u8).collect() };
+        return algorithm
+            .digest(&low_bytes(&key), &low_bytes(&message))
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+    }
+    let mut number = algorithm.digest(key.as_bytes(), message.as_bytes());
+    let mut digits = Vec::new();
+    while number.iter().any(|byte| *byte != 0) {
+        digits.push(characters[div_rem(&mut number, characters.len())]);
+    }
+    digits.into_iter().rev().collect()
+}
+
+/// Replace the letters of the lowercased text with their l33t counterparts.
+fn leetify(text: &str, level: u8) -> String {
+    let replacements = &LEET[usize::from(level) - 1];
+    text.to_lowercase()
+        .chars()
+        .map(|character| match character {
+            'a'..='z' => replacements[character as usize - 'a' as usize].to_string(),
+            _ => character.to_string(),
+        })
+        .collect()
+}
+
+/// Replacements of the letters a to z at each l33t level.
+const LEET: [[&str; 26]; 9] = [
+    [
+        "4", "b", "c", "d", "3", "f", "g", "h", "i", "j", "k", "1", "m", "n", "0", "p", "9", "r",
+        "s", "7", "u", "v", "w", "x", "y", "z",
+    ],
+    [
+        "4", "b", "c", "d", "3", "f", "g", "h", "1", "j", "k", "1", "m", "n", "0", "p", "9", "r",
+        "5", "7", "u", "v", "w", "x", "y", "2",
+    ],
+    [
+        "4", "8", "c", "d", "3", "f", "6", "h", "'", "j", "k", "1", "m", "n", "0", "p", "9", "r",
+        "5", "7", "u", "v", "w", "x", "'/", "2",
+    ],
+    [
+        "@", "8", "c", "d", "3", "f", "6", "h", "'", "j", "k", "1", "m", "n", "0", "p", "9", "r",
+        "5", "7", "u", "v", "w", "x", "'/", "2",
+    ],
+    [
+        "@", "|3", "c", "d", "3", "f", "6", "#", "!", "7", "|<", "1", "m", "n", "0", "|>", "9",
+        "|2", "$", "7", "u", "\\/", "w", "x", "'/", "2",
+    ],
+    [
+        "@", "|3", "c", "|)", "&", "|=", "6", "#", "!", ",|", "|<", "1", "m", "n", "0", "|>", "9",
+        "|2", "$", "7", "u", "\\/", "w", "x", "'/", "2",
+    ],
+    [
+        "@", "|3", "[", "|)", "&", "|=", "6", "#", "!", ",|", "|<", "1", "^^", "^/", "0", "|*",
+        "9", "|2", "5", "7", "(_)", "\\/", "\\/\\/", "><", "'/", "2",
+    ],
+    [
+        "@", "8", "(", "|)", "&", "|=", "6", "|-|", "!", "_|", "|(", "1", "|\\/|", "|\\|", "()",
+        "|>", "(,)", "|2", "$", "|", "|_|", "\\/", "\\^/", ")(", "'/", "\"/_",
+    ],
+    [
+        "@", "8", "(", "|)", "&", "|=", "6", "|-|", "!", "_|", "|{", "|_", "/\\/\\", "|\\|", "()",
+        "|>", "(,)", "|2", "$", "|", "|_|", "\\/", "\\^/", ")(", "'/", "\"/_",
+    ],
+];
+
+/// HMAC of `message` keyed with `key`.
+fn hmac<D: Digest + BlockSizeUser>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = SimpleHmac::<D>::new_from_slice(key).expect("Invalid key length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+/// Hash algorithms of PasswordMaker
+pub enum Algorithm {
+    Md4,
+    HmacMd4,
+    #[default]
+    Md5,
+    /// MD5 as hashed by version 0.6, rendered as hexadecimal
+    Md5V6,
+    HmacMd5,
+    /// HMAC-MD5 as hashed by version 0.6, rendered as hexadecimal
+    HmacMd5V6,
+    Sha1,
+    HmacSha1,
+    Sha256,
+    HmacSha256,
+    Rmd160,
+    HmacRmd160,
+}
+
+impl Algorithm {
+    fn is_hmac(&self) -> bool {
+        matches!(
+            self,
+            Algorithm::HmacMd4
+                | Algorithm::HmacMd5
+                | Algorithm::HmacMd5V6
+                | Algorithm::HmacSha1
+                | Algorithm::HmacSha256
+                | Algorithm::HmacRmd160
+        )
+    }
+
+    fn digest(&self, key: &[u8], message: &[u8]) -> Vec<u8> {
+        match self {
+            Algorithm::Md4 => Md4::digest(message).to_vec(),
+            Algorithm::HmacMd4 => hmac::<Md4>(key, message),
+            Algorithm::Md5 | Algorithm::Md5V6 => Md5::digest(message).to_vec(),
+            Algorithm::HmacMd5 | Algorithm::HmacMd5V6 => hmac::<Md5>(key, message),
+            Algorithm::Sha1 => Sha1::digest(message).to_vec(),
+            Algorithm::HmacSha1 => hmac::<Sha1>(key, message),
+            Algorithm::Sha256 => Sha256::digest(message).to_vec(),
+            Algorithm::HmacSha256 => hmac::<Sha256>(key, message),
+            Algorithm::Rmd160 => Ripemd160::digest(message).to_vec(),
+            Algorithm::HmacRmd160 => hmac::<Ripemd160>(key, message),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Algorithm::Md4 => write!(f, "md4"),
+            Algorithm::HmacMd4 => write!(f, "hmac-md4"),
+            Algorithm::Md5 => write!(f, "md5"),
+            Algorithm::Md5V6 => write!(f, "md5-v6"),
+            Algorithm::HmacMd5 => write!(f, "hmac-md5"),
+            Algorithm::HmacMd5V6 => write!(f, "hmac-md5-v6"),
+            Algorithm::Sha1 => write!(f, "sha1"),
+            Algorithm::HmacSha1 => write!(f, "hmac-sha1"),
+            Algorithm::Sha256 => write!(f, "sha256"),
+            Algorithm::HmacSha256 => write!(f, "hmac-sha256"),
+            Algorithm::Rmd160 => write!(f, "rmd160"),
+            Algorithm::HmacRmd160 => write!(f, "hmac-rmd160"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+/// When l33t is applied
+pub enum LeetMode {
+    #[default]
+    Off,
+    /// To the master password and the text to use
+    BeforeHashing,
+    /// To the generated password
+    AfterHashing,
+    /// Both before and after hashing
+    Both,
+}
+
+impl fmt::Display for LeetMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LeetMode::Off => write!(f, "off"),
+            LeetMode::BeforeHashing => write!(f, "before-hashing"),
+            LeetMode::AfterHashing => write!(f, "after-hashing"),
+            LeetMode::Both => write!(f, "both"),
+        }
+    }
+}
+
+/// Parts of the URL used as the text to use.
+///
+/// The domain is the last two labels of the host name, the subdomains the
+/// labels before them. The path includes the port, query and fragment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UrlComponents {
+    pub protocol: bool,
+    pub subdomains: bool,
+    pub domain: bool,
+    pub path: bool,
+}
+
+impl Default for UrlComponents {
+    fn default() -> Self {
+        Self {
+            protocol: false,
+            subdomains: false,
+            domain: true,
+            path: false,
+        }
+    }
+}
+
+impl From<&HostnameConfig> for UrlComponents {
+    /// Use the whole URL with passthrough, and the subdomains if they're
+    /// kept.
+    fn from(config: &HostnameConfig) -> Self {
+        Self {
+            protocol: config.passthrough,
+            subdomains: config.passthrough || config.keep_subdomains,
+            domain: true,
+            path: config.passthrough,
+        }
+    }
+}
+
+impl UrlComponents {
+    /// Recombine the selected parts of the URL, the way PasswordMaker does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustgenpass::passwordmaker::UrlComponents;
+    /// let components = UrlComponents { subdomains: true, ..UrlComponents::default() };
+    /// assert_eq!("www.example.com", components.text_to_use("https://www.example.com/login"));
+    /// ```
+    pub fn text_to_use(&self, url: &str) -> String {
+        let (protocol, rest) = url.split_once(':').unwrap_or(("", url));
+        let has_authority = rest.starts_with("//");
+        let rest = rest.strip_prefix("//").unwrap_or(rest);
+        // A URL with a protocol but no authority, like mailto:, is all path.
+        let path_start = if url.contains(':') && !has_authority {
+            Some(0)
+        } else {
+            rest.find('/')
+        };
+        let (authority, path) = path_start.map_or((rest, ""), |index| rest.split_at(index));
+        let host_and_port = authority
+            .split_once('@')
+            .map_or(authority, |(_, host)| host);
+        let (host, port) = host_and_port.split_once(':').unwrap_or((host_and_port, ""));
+        let (subdomains, domain) = match host.rmatch_indices('.').nth(1) {
+            Some((index, _)) => (&host[..index], &host[index + 1..]),
+            None => ("", host),
+        };
+
+        let protocol = if self.protocol { protocol } else { "" };
+        let subdomains = if self.subdomains { subdomains } else { "" };
+        let domain = if self.domain { domain } else { "" };
+        let (port, path) = if self.path { (port, path) } else { ("", "") };
+        let mut text = String::new();
+        if !protocol.is_empty() {
+            text.push_str(protocol);
+            text.push_str("://");
+        }
+        text.push_str(subdomains);
+        if !subdomains.is_empty() && !domain.is_empty() {
+            text.push('.');
+        }
+        text.push_str(domain);
+        let has_host = !subdomains.is_empty() || !domain.is_empty();
+        if has_host && !port.is_empty() {
+            text.push(':');
+        }
+        text.push_str(port);
+        text.push_str(path);
+        text
+    }
+}
+
+/// Profile PasswordMaker can't generate a password for.
+#[derive(Debug, PartialEq)]
+pub enum PasswordMakerError {
+    InsufficientCharacters,
+    InvalidLeetLevel(u8),
+    EmptyText,
+}
+
+impl fmt::Display for PasswordMakerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PasswordMakerError::InsufficientCharacters => {
+                write!(f, "PasswordMaker requires at least two characters")
+            }
+            PasswordMakerError::InvalidLeetLevel(level) => {
+                write!(
+                    f,
+                    "Invalid l33t level for PasswordMaker: {}, min: 1, max: 9",
+                    level
+                )
+            }
+            PasswordMakerError::EmptyText => {
+                write!(f, "No text to use, the selected parts of the URL are empty")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PasswordMakerError {}
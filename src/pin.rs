@@ -0,0 +1,265 @@
+//! Numeric PINs, for phone banking and door systems.
+//!
+//! The final hash is generated like the SuperGenPass password, with the same
+//! rounds and password policy. Digits are taken from the bytes of its SHA-512
+//! digest, skipping bytes of 250 and above so that every digit is equally
+//! likely, and the digest is hashed again when it runs out of bytes.
+//!
+//! Weak PINs can be rejected, generating the next PIN instead: repeated
+//! patterns like `1111` or `1212`, sequences like `1234` or `8765`, and PINs
+//! looking like dates or years, such as `0412` or `1987`.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustgenpass::GenerateConfig;
+//! use rustgenpass::pin::generate;
+//! let config = GenerateConfig { length: 4, ..GenerateConfig::default() };
+//! assert_eq!("3793", generate("masterpassword", "example.com", config, true).unwrap().password);
+//! ```
+
+use crate::{GenerateConfig, GeneratedPassword, final_hash};
+use sha2::{Digest, Sha512};
+use std::{collections::HashSet, fmt};
+
+/// Shortest PIN.
+pub const MIN_LENGTH: usize = 4;
+
+/// Longest PIN.
+pub const MAX_LENGTH: usize = 8;
+
+/// Generate a PIN from the final SuperGenPass hash.
+///
+/// # Arguments
+///
+/// * `password` - Master password to generate PIN from
+/// * `domain` - Domain to generate PIN for
+/// * `config` - Secret, rounds and hash algorithm, and the number of digits
+/// * `reject_weak` - Skip PINs with repeated patterns, sequences or dates
+pub fn generate(
+    password: &str,
+    domain: &str,
+    config: GenerateConfig,
+    reject_weak: bool,
+) -> Result<GeneratedPassword, PinError> {
+    if !(MIN_LENGTH..=MAX_LENGTH).contains(&config.length) {
+        return Err(PinError::InvalidLength(config.length));
+    }
+    let (hash, rounds) = final_hash(password, domain, &config);
+    let mut digits = Digits::new(hash.as_bytes());
+    loop {
+        let pin: String = (0..config.length).map(|_| digits.next()).collect();
+        if !reject_weak || !is_weak(&pin) {
+            return Ok(GeneratedPassword {
+                password: pin,
+                rounds,
+            });
+        }
+    }
+}
+
+/// Whether the PIN is a repeated pattern, a sequence or looks like a date.
+/// Anything but ASCII digits isn't a PIN and never weak.
+///
+/// # Examples
+///
+/// ```
+/// use rustgenpass::pin::is_weak;
+/// assert!(is_weak("1212"));
+/// assert!(is_weak("3456"));
+/// assert!(is_weak("1987"));
+/// assert!(!is_weak("3793"));
+/// assert!(!is_weak("a0"));
+/// ```
+pub fn is_weak(pin: &str) -> bool {
+    if !pin.bytes().all(|byte| byte.is_ascii_digit()) {
+        return false;
+    }
+    let digits: Vec<u8> = pin.bytes().map(|byte| byte - b'0').collect();
+    is_repeated(&digits) || is_sequence(&digits) || is_date(&digits)
+}
+
+/// Entropy in bits of the PINs of `length` digits.
+///
+/// # Examples
+///
+/// ```
+/// use rustgenpass::pin::entropy;
+/// assert_eq!(13.3, (entropy(4, false) * 10.0).round() / 10.0);
+/// assert_eq!(13.2, (entropy(4, true) * 10.0).round() / 10.0);
+/// ```
+pub fn entropy(length: usize, reject_weak: bool) -> f64 {
+    let all = 10_f64.powi(length as i32);
+    if reject_weak {
+        (all - weak_pins(length).len() as f64).log2()
+    } else {
+        all.log2()
+    }
+}
+
+/// Digits taken from a digest without modulo bias.
+struct Digits {
+    digest: [u8; 64],
+    position: usize,
+}
+
+impl Digits {
+    fn new(hash: &[u8]) -> Self {
+        Self {
+            digest: Sha512::digest(hash).into(),
+            position: 0,
+        }
+    }
+
+    fn next(&mut self) -> char {
+        loop {
+            if self.position == self.digest.len() {
+                self.digest = Sha512::digest(self.digest).into();
+                self.position = 0;
+            }
+            let byte = self.digest[self.position];
+            self.position += 1;
+            if byte < 250 {
+                return char::from(b'0' + byte % 10);
+            }
+        }
+    }
+}
+
+/// The PIN repeats a pattern of at most half its length, like `1111`,
+/// `1212` or `12312`.
+fn is_repeated(digits: &[u8]) -> bool {
+    (1..=digits.len() / 2)
+        .any(|period| (period..digits.len()).all(|i| digits[i] == digits[i - period]))
+}
+
+/// Every digit follows the previous one up or down, like `1234`, `8765` or
+/// `8901`.
+fn is_sequence(digits: &[u8]) -> bool {
+    [1, 9].into_iter().any(|step| {
+        digits
+            .windows(2)
+            .all(|pair| pair[1] == (pair[0] + step) % 10)
+    })
+}
+
+/// The PIN is a day and month, with a year of two or four digits for longer
+/// PINs, or a year on its own.
+fn is_date(digits: &[u8]) -> bool {
+    let number = |range: std::ops::Range<usize>| {
+        digits[range]
+            .iter()
+            .fold(0_u32, |number, digit| number * 10 + u32::from(*digit))
+    };
+    let day_month = |day, month| is_day_of_month(number(day), number(month));
+    let year = |range| (1900..=2099).contains(&number(range));
+    match digits.len() {
+        4 => day_month(0..2, 2..4) || day_month(2..4, 0..2) || year(0..4),
+        6 => day_month(0..2, 2..4) || day_month(2..4, 0..2) || day_month(4..6, 2..4),
+        8 => {
+            (year(4..8) && (day_month(0..2, 2..4) || day_month(2..4, 0..2)))
+                || (year(0..4) && day_month(6..8, 4..6))
+        }
+        _ => false,
+    }
+}
+
+fn is_day_of_month(day: u32, month: u32) -> bool {
+    let days = match month {
+        2 => 29,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => 0,
+    };
+    (1..=days).contains(&day)
+}
+
+/// All weak PINs of `length` digits.
+fn weak_pins(length: usize) -> HashSet<String> {
+    let mut pins = HashSet::new();
+    for period in 1..=length / 2 {
+        for pattern in 0..10_usize.pow(period as u32) {
+            let pattern = format!("{:0width$}", pattern, width = period);
+            pins.insert(pattern.repeat(length).chars().take(length).collect());
+        }
+    }
+    for first in 0..10 {
+        for step in [1, 9] {
+            pins.insert(
+                (0..length)
+                    .map(|i| char::from(b'0' + ((first + step * i) % 10) as u8))
+                    .collect(),
+            );
+        }
+    }
+    let days: Vec<(u32, u32)> = (1..=12)
+        .flat_map(|month| {
+            (1..=31)
+                .filter(move |day| is_day_of_month(*day, month))
+                .map(move |day| (day, month))
+        })
+        .collect();
+    for (day, month) in &days {
+        match length {
+            4 => {
+                pins.insert(format!("{:02}{:02}", day, month));
+                pins.insert(format!("{:02}{:02}", month, day));
+            }
+            6 => {
+                for year in 0..100 {
+                    pins.insert(format!("{:02}{:02}{:02}", day, month, year));
+                    pins.insert(format!("{:02}{:02}{:02}", month, day, year));
+                    pins.insert(format!("{:02}{:02}{:02}", year, month, day));
+                }
+            }
+            8 => {
+                for year in 1900..=2099 {
+                    pins.insert(format!("{:02}{:02}{}", day, month, year));
+                    pins.insert(format!("{:02}{:02}{}", month, day, year));
+                    pins.insert(format!("{}{:02}{:02}", year, month, day));
+                }
+            }
+            _ => {}
+        }
+    }
+    if length == 4 {
+        pins.extend((1900..=2099).map(|year| year.to_string()));
+    }
+    pins
+}
+
+/// Configuration no PIN can be generated for.
+#[derive(Debug, PartialEq)]
+pub enum PinError {
+    InvalidLength(usize),
+}
+
+impl fmt::Display for PinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PinError::InvalidLength(length) => write!(
+                f,
+                "Invalid length for a PIN: {}, min: {}, max: {}",
+                length, MIN_LENGTH, MAX_LENGTH
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PinError {}
+
+#[cfg(test)]
+mod test_weak_pins {
+    use super::*;
+
+    #[test]
+    fn lists_all_weak_pins() {
+        for length in MIN_LENGTH..=6 {
+            let weak = (0..10_usize.pow(length as u32))
+                .map(|pin| format!("{:0width$}", pin, width = length))
+                .filter(|pin| is_weak(pin))
+                .collect::<HashSet<_>>();
+            assert_eq!(weak, weak_pins(length), "{}", length);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Passwords of the [PwdHash](https://pwdhash.github.io/website/) browser
+//! extension.
+//!
+//! The password is the base64 encoded HMAC-MD5 of the domain, keyed with the
+//! master password. It is as long as the master password with the `@@`
+//! prefix typed to trigger the extension, and contains an uppercase letter,
+//! a lowercase letter and a numeral. It contains other characters only if the
+//! master password does.
+//!
+//! The implementation is only checked against a port of the JavaScript of the
+//! extension, not against published test vectors.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustgenpass::{get_hostname, pwdhash};
+//! let domain = get_hostname("https://www.example.com/foo/bar.html").unwrap();
+//! assert_eq!("VNPpJ4Wmq2r5t2XH", pwdhash::generate("masterpassword", &domain));
+//! ```
+
+use hmac::{Hmac, Mac};
+use md5::Md5;
+
+/// Prefix typed before the master password to trigger the extension, which
+/// counts towards the password length.
+const PASSWORD_PREFIX: &str = "@@";
+
+/// Generate a PwdHash password.
+///
+/// # Arguments
+///
+/// * `password` - Master password to generate hashed password from, without
+///   the `@@` prefix
+/// * `domain` - Domain to generate password for
+pub fn generate<S: Into<String>>(password: S, domain: S) -> String {
+    let password = password.into();
+    let mut mac =
+        Hmac::<Md5>::new_from_slice(&extension_bytes(&password)).expect("Invalid key length");
+    mac.update(&extension_bytes(&domain.into()));
+    let hash = base64::encode(mac.finalize().into_bytes());
+    let size = password.encode_utf16().count() + PASSWORD_PREFIX.len();
+    let nonalphanumeric = password.chars().any(is_nonalphanumeric);
+    apply_constraints(hash.trim_end_matches('='), size, nonalphanumeric)
+}
+
+/// Bytes hashed by the extension, which keeps the low byte of each UTF-16
+/// code unit.
+fn extension_bytes(text: &str) -> Vec<u8> {
+    text.encode_utf16().map(|unit| unit as u8).collect()
+}
+
+/// Characters matched by `\W` in JavaScript regular expressions.
+fn is_nonalphanumeric(character: char) -> bool {
+    !character.is_ascii_alphanumeric() && character != '_'
+}
+
+/// Make the hash `size` characters long and add the required character
+/// classes, using the characters beyond the first `size - 4` characters.
+fn apply_constraints(hash: &str, size: usize, nonalphanumeric: bool) -> String {
+    let starting_size = size.saturating_sub(4).min(hash.len());
+    let mut result: Vec<char> = hash[..starting_size].chars().collect();
+    let mut extras = hash[starting_size..].chars();
+    // The extension uses the NUL character once the hash runs out.
+    let mut next_extra = move || extras.next().unwrap_or('\0');
+    let between = |min: char, interval: u32, offset: char| {
+        char::from_u32(min as u32 + offset as u32 % interval).expect("Invalid character")
+    };
+
+    let extra = next_extra();
+    result.push(match result.iter().any(char::is_ascii_uppercase) {
+        true => extra,
+        false => between('A', 26, extra),
+    });
+    let extra = next_extra();
+    result.push(match result.iter().any(char::is_ascii_lowercase) {
+        true => extra,
+        false => between('a', 26, extra),
+    });
+    let extra = next_extra();
+    result.push(match result.iter().any(char::is_ascii_digit) {
+        true => extra,
+        false => between('0', 10, extra),
+    });
+    if nonalphanumeric && result.iter().any(|c| is_nonalphanumeric(*c)) {
+        result.push(next_extra());
+    } else {
+        result.push('+');
+    }
+    if !nonalphanumeric {
+        while let Some(position) = result.iter().position(|c| is_nonalphanumeric(*c)) {
+            result[position] = between('A', 26, next_extra());
+        }
+    }
+
+    // Rotate the result to make it harder to guess the inserted locations.
+    let length = result.len();
+    result.rotate_left(next_extra() as usize % length);
+    result.into_iter().collect()
+}
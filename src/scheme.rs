@@ -0,0 +1,555 @@
+//! Generation schemes behind one interface, looked up by name.
+//!
+//! A [`Scheme`] generates the password of a site from the master password,
+//! the site and parameters of its own. Parameters are given as strings, like
+//! options on the command line, and parameters a scheme doesn't accept are
+//! rejected. The [`Registry`] holds the schemes of this crate, and any
+//! schemes registered with it.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustgenpass::scheme::{Params, Registry};
+//! let registry = Registry::default();
+//! let params = Params::from([("length".to_string(), "16".to_string())]);
+//! let scheme = registry.get("supergenpass").unwrap();
+//! let generated = scheme.generate("masterpassword", "example.com", &params).unwrap();
+//! assert_eq!("jHMOHn7bRszh9PiX", generated.password);
+//! ```
+
+use crate::answer::{self, AnswerError};
+use crate::charset::{self, CharsetError};
+use crate::lesspass::{self, LessPassError};
+use crate::passphrase::{self, Capitalization, PassphraseConfig, PassphraseError};
+use crate::passwordmaker::{self, PasswordMakerError};
+use crate::pin::{self, PinError};
+use crate::spectre::{self, Template};
+use crate::{GenerateConfig, GeneratedPassword, HashAlgorithm, generate_with_details, pwdhash};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+/// Parameters of a scheme, by name.
+pub type Params = BTreeMap<String, String>;
+
+/// Algorithm generating passwords for sites from a master password.
+pub trait Scheme: Send + Sync {
+    /// Name the scheme is registered under.
+    fn name(&self) -> &'static str;
+
+    /// Names of the parameters the scheme accepts.
+    fn params(&self) -> &'static [&'static str];
+
+    /// Generate the password of a site.
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - Master password
+    /// * `site` - Site identity, usually the isolated domain
+    /// * `params` - Parameters of the scheme, defaults are used for missing ones
+    fn generate(
+        &self,
+        password: &str,
+        site: &str,
+        params: &Params,
+    ) -> Result<GeneratedPassword, SchemeError>;
+}
+
+/// Schemes looked up by name.
+pub struct Registry {
+    schemes: Vec<Box<dyn Scheme>>,
+}
+
+impl Default for Registry {
+    /// Registry of the schemes of this crate.
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(SuperGenPass));
+        registry.register(Box::new(PwdHash));
+        registry.register(Box::new(Spectre));
+        registry.register(Box::new(LessPass));
+        registry.register(Box::new(PasswordMaker));
+        registry
+    }
+}
+
+impl Registry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self {
+            schemes: Vec::new(),
+        }
+    }
+
+    /// Register a scheme, replacing any scheme registered with the same name.
+    pub fn register(&mut self, scheme: Box<dyn Scheme>) {
+        self.schemes
+            .retain(|registered| registered.name() != scheme.name());
+        self.schemes.push(scheme);
+    }
+
+    /// Look up a scheme by name.
+    pub fn get(&self, name: &str) -> Result<&dyn Scheme, SchemeError> {
+        self.schemes
+            .iter()
+            .find(|scheme| scheme.name() == name)
+            .map(|scheme| scheme.as_ref())
+            .ok_or_else(|| SchemeError::UnknownScheme(name.to_string()))
+    }
+
+    /// Names of the registered schemes, in the order they were registered.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.schemes.iter().map(|scheme| scheme.name())
+    }
+}
+
+/// Format of the passwords of the [`SuperGenPass`] scheme.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Format {
+    /// Start of the base64 encoded hash
+    #[default]
+    Password,
+    /// Words of the EFF large wordlist, see [`passphrase`]
+    Passphrase,
+    /// Digits only, see [`pin`]
+    Pin,
+    /// Answer to the security question of the `question` parameter, see
+    /// [`answer`]
+    #[cfg_attr(feature = "cli", value(skip))]
+    Answer,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Format::Password => write!(f, "password"),
+            Format::Passphrase => write!(f, "passphrase"),
+            Format::Pin => write!(f, "pin"),
+            Format::Answer => write!(f, "answer"),
+        }
+    }
+}
+
+/// [SuperGenPass](https://chriszarate.github.io/supergenpass/), with the
+/// `length`, `rounds`, `hash` and `secret` parameters, and the `format`
+/// parameter with the `charset` parameter of passwords, the `words`,
+/// `separator`, `capitalize` and `digit` parameters of passphrases, the
+/// `pin-length` and `reject-weak` parameters of PINs and the `question`
+/// parameter of answers.
+pub struct SuperGenPass;
+
+impl Scheme for SuperGenPass {
+    fn name(&self) -> &'static str {
+        "supergenpass"
+    }
+
+    fn params(&self) -> &'static [&'static str] {
+        &[
+            "length",
+            "rounds",
+            "hash",
+            "secret",
+            "format",
+            "charset",
+            "words",
+            "separator",
+            "capitalize",
+            "digit",
+            "pin-length",
+            "reject-weak",
+            "question",
+        ]
+    }
+
+    fn generate(
+        &self,
+        password: &str,
+        site: &str,
+        params: &Params,
+    ) -> Result<GeneratedPassword, SchemeError> {
+        let params = Reader::new(self, params)?;
+        let length = params.parse("length", 10)?;
+        if !(4..=24).contains(&length) {
+            return Err(SchemeError::InvalidParam("length", length.to_string()));
+        }
+        let config = GenerateConfig {
+            secret: params.string("secret").map(String::from),
+            length,
+            hash_rounds: params.parse("rounds", 10)?,
+            hash_algorithm: params.choice(
+                "hash",
+                [HashAlgorithm::MD5, HashAlgorithm::SHA512],
+                HashAlgorithm::MD5,
+            )?,
+        };
+        match params.choice(
+            "format",
+            [
+                Format::Password,
+                Format::Passphrase,
+                Format::Pin,
+                Format::Answer,
+            ],
+            Format::Password,
+        )? {
+            Format::Password => match params.string("charset") {
+                Some(characters) => Ok(charset::generate(password, site, config, characters)?),
+                None => Ok(generate_with_details(password, site, config)),
+            },
+            Format::Passphrase => {
+                let passphrase = PassphraseConfig {
+                    words: params.parse("words", 6)?,
+                    separator: params.string("separator").unwrap_or("-").to_string(),
+                    capitalization: params.choice(
+                        "capitalize",
+                        [
+                            Capitalization::Lower,
+                            Capitalization::Title,
+                            Capitalization::Upper,
+                        ],
+                        Capitalization::Lower,
+                    )?,
+                    digit: params.parse("digit", false)?,
+                };
+                Ok(passphrase::generate(password, site, config, &passphrase)?)
+            }
+            Format::Pin => {
+                let config = GenerateConfig {
+                    length: params.parse("pin-length", 4)?,
+                    ..config
+                };
+                let reject_weak = params.parse("reject-weak", false)?;
+                Ok(pin::generate(password, site, config, reject_weak)?)
+            }
+            Format::Answer => {
+                let question = params
+                    .string("question")
+                    .ok_or(SchemeError::MissingParam(self.name(), "question"))?;
+                Ok(answer::generate(password, site, config, question)?)
+            }
+        }
+    }
+}
+
+/// [PwdHash](crate::pwdhash), without parameters.
+pub struct PwdHash;
+
+impl Scheme for PwdHash {
+    fn name(&self) -> &'static str {
+        "pwdhash"
+    }
+
+    fn params(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn generate(
+        &self,
+        password: &str,
+        site: &str,
+        params: &Params,
+    ) -> Result<GeneratedPassword, SchemeError> {
+        Reader::new(self, params)?;
+        Ok(single_round(pwdhash::generate(password, site)))
+    }
+}
+
+/// [Spectre](crate::spectre), with the required `full-name`, and the
+/// `counter` and `template` parameters.
+pub struct Spectre;
+
+impl Scheme for Spectre {
+    fn name(&self) -> &'static str {
+        "spectre"
+    }
+
+    fn params(&self) -> &'static [&'static str] {
+        &["full-name", "counter", "template"]
+    }
+
+    fn generate(
+        &self,
+        password: &str,
+        site: &str,
+        params: &Params,
+    ) -> Result<GeneratedPassword, SchemeError> {
+        let params = Reader::new(self, params)?;
+        let full_name = params
+            .string("full-name")
+            .ok_or(SchemeError::MissingParam(self.name(), "full-name"))?;
+        let template = params.choice(
+            "template",
+            [
+                Template::Maximum,
+                Template::Long,
+                Template::Medium,
+                Template::Short,
+                Template::Basic,
+                Template::Pin,
+                Template::Name,
+                Template::Phrase,
+            ],
+            Template::Long,
+        )?;
+        Ok(single_round(spectre::generate(
+            full_name,
+            password,
+            site,
+            params.parse("counter", 1)?,
+            &template,
+        )))
+    }
+}
+
+/// [LessPass](crate::lesspass), with the `login`, `counter`, `length`,
+/// `no-lowercase`, `no-uppercase`, `no-digits` and `no-symbols` parameters.
+pub struct LessPass;
+
+impl Scheme for LessPass {
+    fn name(&self) -> &'static str {
+        "lesspass"
+    }
+
+    fn params(&self) -> &'static [&'static str] {
+        &[
+            "login",
+            "counter",
+            "length",
+            "no-lowercase",
+            "no-uppercase",
+            "no-digits",
+            "no-symbols",
+        ]
+    }
+
+    fn generate(
+        &self,
+        password: &str,
+        site: &str,
+        params: &Params,
+    ) -> Result<GeneratedPassword, SchemeError> {
+        let params = Reader::new(self, params)?;
+        let profile = lesspass::Profile {
+            lowercase: !params.parse("no-lowercase", false)?,
+            uppercase: !params.parse("no-uppercase", false)?,
+            digits: !params.parse("no-digits", false)?,
+            symbols: !params.parse("no-symbols", false)?,
+            length: params.parse("length", 16)?,
+            counter: params.parse("counter", 1)?,
+        };
+        let login = params.string("login").unwrap_or_default();
+        Ok(single_round(lesspass::generate(
+            password, site, login, &profile,
+        )?))
+    }
+}
+
+/// [PasswordMaker](crate::passwordmaker), with the site as the text to use,
+/// and the `algorithm`, `charset`, `login`, `modifier`, `prefix`, `suffix`,
+/// `leet`, `leet-level` and `length` parameters.
+pub struct PasswordMaker;
+
+impl Scheme for PasswordMaker {
+    fn name(&self) -> &'static str {
+        "passwordmaker"
+    }
+
+    fn params(&self) -> &'static [&'static str] {
+        &[
+            "algorithm",
+            "charset",
+            "login",
+            "modifier",
+            "prefix",
+            "suffix",
+            "leet",
+            "leet-level",
+            "length",
+        ]
+    }
+
+    fn generate(
+        &self,
+        password: &str,
+        site: &str,
+        params: &Params,
+    ) -> Result<GeneratedPassword, SchemeError> {
+        use passwordmaker::{Algorithm, LeetMode};
+
+        let params = Reader::new(self, params)?;
+        let string = |key| params.string(key).unwrap_or_default().to_string();
+        let profile = passwordmaker::Profile {
+            algorithm: params.choice(
+                "algorithm",
+                [
+                    Algorithm::Md4,
+                    Algorithm::HmacMd4,
+                    Algorithm::Md5,
+                    Algorithm::Md5V6,
+                    Algorithm::HmacMd5,
+                    Algorithm::HmacMd5V6,
+                    Algorithm::Sha1,
+                    Algorithm::HmacSha1,
+                    Algorithm::Sha256,
+                    Algorithm::HmacSha256,
+                    Algorithm::Rmd160,
+                    Algorithm::HmacRmd160,
+                ],
+                Algorithm::Md5,
+            )?,
+            leet: params.choice(
+                "leet",
+                [
+                    LeetMode::Off,
+                    LeetMode::BeforeHashing,
+                    LeetMode::AfterHashing,
+                    LeetMode::Both,
+                ],
+                LeetMode::Off,
+            )?,
+            leet_level: params.parse("leet-level", 1)?,
+            characters: params
+                .string("charset")
+                .unwrap_or(passwordmaker::DEFAULT_CHARACTERS)
+                .to_string(),
+            username: string("login"),
+            modifier: string("modifier"),
+            length: params.parse("length", 8)?,
+            prefix: string("prefix"),
+            suffix: string("suffix"),
+            ..passwordmaker::Profile::default()
+        };
+        Ok(single_round(passwordmaker::generate_with_text(
+            password, site, &profile,
+        )?))
+    }
+}
+
+fn single_round(password: String) -> GeneratedPassword {
+    GeneratedPassword {
+        password,
+        rounds: 1,
+    }
+}
+
+/// Typed access to the parameters of a scheme.
+struct Reader<'a> {
+    params: &'a Params,
+}
+
+impl<'a> Reader<'a> {
+    /// Check that the scheme accepts all parameters.
+    fn new(scheme: &dyn Scheme, params: &'a Params) -> Result<Self, SchemeError> {
+        match params
+            .keys()
+            .find(|key| !scheme.params().contains(&key.as_str()))
+        {
+            Some(key) => Err(SchemeError::UnknownParam(scheme.name(), key.clone())),
+            None => Ok(Self { params }),
+        }
+    }
+
+    fn string(&self, key: &str) -> Option<&'a str> {
+        self.params.get(key).map(String::as_str)
+    }
+
+    fn parse<T: FromStr>(&self, key: &'static str, default: T) -> Result<T, SchemeError> {
+        match self.string(key) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| SchemeError::InvalidParam(key, value.to_string())),
+            None => Ok(default),
+        }
+    }
+
+    /// Parse a parameter as one of `choices`, by its name when displayed.
+    fn choice<T: fmt::Display, const N: usize>(
+        &self,
+        key: &'static str,
+        choices: [T; N],
+        default: T,
+    ) -> Result<T, SchemeError> {
+        match self.string(key) {
+            Some(value) => choices
+                .into_iter()
+                .find(|choice| choice.to_string().eq_ignore_ascii_case(value))
+                .ok_or_else(|| SchemeError::InvalidParam(key, value.to_string())),
+            None => Ok(default),
+        }
+    }
+}
+
+/// Scheme that couldn't generate a password.
+#[derive(Debug, PartialEq)]
+pub enum SchemeError {
+    UnknownScheme(String),
+    UnknownParam(&'static str, String),
+    MissingParam(&'static str, &'static str),
+    InvalidParam(&'static str, String),
+    LessPass(LessPassError),
+    PasswordMaker(PasswordMakerError),
+    Passphrase(PassphraseError),
+    Pin(PinError),
+    Charset(CharsetError),
+    Answer(AnswerError),
+}
+
+impl fmt::Display for SchemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemeError::UnknownScheme(name) => write!(f, "Unknown scheme: {}", name),
+            SchemeError::UnknownParam(scheme, key) => {
+                write!(f, "Unknown parameter for the {} scheme: {}", scheme, key)
+            }
+            SchemeError::MissingParam(scheme, key) => {
+                write!(f, "The {} scheme requires {}", scheme, key)
+            }
+            SchemeError::InvalidParam(key, value) => {
+                write!(f, "Invalid value for {}: {}", key, value)
+            }
+            SchemeError::LessPass(err) => write!(f, "{}", err),
+            SchemeError::PasswordMaker(err) => write!(f, "{}", err),
+            SchemeError::Passphrase(err) => write!(f, "{}", err),
+            SchemeError::Pin(err) => write!(f, "{}", err),
+            SchemeError::Charset(err) => write!(f, "{}", err),
+            SchemeError::Answer(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for SchemeError {}
+
+impl From<LessPassError> for SchemeError {
+    fn from(err: LessPassError) -> Self {
+        SchemeError::LessPass(err)
+    }
+}
+
+impl From<PasswordMakerError> for SchemeError {
+    fn from(err: PasswordMakerError) -> Self {
+        SchemeError::PasswordMaker(err)
+    }
+}
+
+impl From<PassphraseError> for SchemeError {
+    fn from(err: PassphraseError) -> Self {
+        SchemeError::Passphrase(err)
+    }
+}
+
+impl From<PinError> for SchemeError {
+    fn from(err: PinError) -> Self {
+        SchemeError::Pin(err)
+    }
+}
+
+impl From<CharsetError> for SchemeError {
+    fn from(err: CharsetError) -> Self {
+        SchemeError::Charset(err)
+    }
+}
+
+impl From<AnswerError> for SchemeError {
+    fn from(err: AnswerError) -> Self {
+        SchemeError::Answer(err)
+    }
+}
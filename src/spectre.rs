@@ -0,0 +1,191 @@
+//! Passwords compatible with [Spectre](https://spectre.app/), formerly
+//! Master Password, using version 3 of its algorithm.
+//!
+//! A master key is derived from the full name of the user and the master
+//! password with scrypt, which is slow on purpose. Site passwords are
+//! rendered from an HMAC-SHA256 of the site name and a counter, using one of
+//! the templates of the chosen [`Template`] class.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustgenpass::spectre::{MasterKey, Template};
+//! let key = MasterKey::new("Robert Lee Mitchell", "banana colored duckling");
+//! assert_eq!("Jejr5[RepuSosp", key.site_password("masterpasswordapp.com", 1, &Template::Long));
+//! assert_eq!("7662", key.site_password("masterpasswordapp.com", 1, &Template::Pin));
+//! ```
+
+use hmac::{Hmac, Mac};
+use scrypt::Params;
+use sha2::Sha256;
+use std::fmt;
+
+/// Scope of keys and seeds for site passwords.
+const SCOPE: &[u8] = b"com.lyndir.masterpassword";
+
+/// Generate a site password, deriving the master key for it.
+///
+/// # Arguments
+///
+/// * `full_name` - Full name of the user
+/// * `password` - Master password
+/// * `site` - Site name, usually the domain
+/// * `counter` - Counter of the site password, starting at 1
+/// * `template` - Template class of the site password
+///
+/// # Examples
+///
+/// ```
+/// use rustgenpass::spectre::{generate, Template};
+/// let password = generate("Robert Lee Mitchell", "banana colored duckling", "masterpasswordapp.com", 1, &Template::Long);
+/// assert_eq!("Jejr5[RepuSosp", password);
+/// ```
+pub fn generate(
+    full_name: &str,
+    password: &str,
+    site: &str,
+    counter: u32,
+    template: &Template,
+) -> String {
+    MasterKey::new(full_name, password).site_password(site, counter, template)
+}
+
+/// Master key of a user, deriving any number of site passwords.
+pub struct MasterKey([u8; 64]);
+
+impl MasterKey {
+    /// Derive the master key of a user.
+    pub fn new(full_name: &str, password: &str) -> Self {
+        let params = Params::new(15, 8, 2, 64).expect("Invalid scrypt parameters");
+        let mut key = [0; 64];
+        scrypt::scrypt(
+            password.as_bytes(),
+            &scoped(full_name.as_bytes()),
+            &params,
+            &mut key,
+        )
+        .expect("Invalid key length");
+        Self(key)
+    }
+
+    /// Render the password of a site.
+    pub fn site_password(&self, site: &str, counter: u32, template: &Template) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.0).expect("Invalid key length");
+        mac.update(&scoped(site.as_bytes()));
+        mac.update(&counter.to_be_bytes());
+        let seed = mac.finalize().into_bytes();
+
+        let templates = template.templates();
+        let template = templates[usize::from(seed[0]) % templates.len()];
+        template
+            .bytes()
+            .zip(&seed[1..])
+            .map(|(class, byte)| {
+                let characters = characters(class).as_bytes();
+                char::from(characters[usize::from(*byte) % characters.len()])
+            })
+            .collect()
+    }
+}
+
+/// Scope followed by the length of the value and the value.
+fn scoped(value: &[u8]) -> Vec<u8> {
+    let length = u32::try_from(value.len()).expect("Value too long");
+    [SCOPE, &length.to_be_bytes(), value].concat()
+}
+
+/// Characters of a character class in a template.
+fn characters(class: u8) -> &'static str {
+    match class {
+        b'V' => "AEIOU",
+        b'C' => "BCDFGHJKLMNPQRSTVWXYZ",
+        b'v' => "aeiou",
+        b'c' => "bcdfghjklmnpqrstvwxyz",
+        b'A' => "AEIOUBCDFGHJKLMNPQRSTVWXYZ",
+        b'a' => "AEIOUaeiouBCDFGHJKLMNPQRSTVWXYZbcdfghjklmnpqrstvwxyz",
+        b'n' => "0123456789",
+        b'o' => "@&%?,=[]_:-+*$#!'^~;()/.",
+        b'x' => "AEIOUaeiouBCDFGHJKLMNPQRSTVWXYZbcdfghjklmnpqrstvwxyz0123456789!@#$%^&*()",
+        b' ' => " ",
+        _ => unreachable!("Unknown character class"),
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+/// Template classes of site passwords
+pub enum Template {
+    /// 20 characters, containing symbols
+    Maximum,
+    /// Copy-friendly, 14 characters, containing symbols
+    #[default]
+    Long,
+    /// Copy-friendly, 8 characters, containing symbols
+    Medium,
+    /// Copy-friendly, 4 characters, no symbols
+    Short,
+    /// 8 characters, no symbols
+    Basic,
+    /// 4 numbers
+    Pin,
+    /// 9 letter name
+    Name,
+    /// 20 character sentence
+    Phrase,
+}
+
+impl Template {
+    fn templates(&self) -> &'static [&'static str] {
+        match self {
+            Template::Maximum => &["anoxxxxxxxxxxxxxxxxx", "axxxxxxxxxxxxxxxxxno"],
+            Template::Long => &[
+                "CvcvnoCvcvCvcv",
+                "CvcvCvcvnoCvcv",
+                "CvcvCvcvCvcvno",
+                "CvccnoCvcvCvcv",
+                "CvccCvcvnoCvcv",
+                "CvccCvcvCvcvno",
+                "CvcvnoCvccCvcv",
+                "CvcvCvccnoCvcv",
+                "CvcvCvccCvcvno",
+                "CvcvnoCvcvCvcc",
+                "CvcvCvcvnoCvcc",
+                "CvcvCvcvCvccno",
+                "CvccnoCvccCvcv",
+                "CvccCvccnoCvcv",
+                "CvccCvccCvcvno",
+                "CvcvnoCvccCvcc",
+                "CvcvCvccnoCvcc",
+                "CvcvCvccCvccno",
+                "CvccnoCvcvCvcc",
+                "CvccCvcvnoCvcc",
+                "CvccCvcvCvccno",
+            ],
+            Template::Medium => &["CvcnoCvc", "CvcCvcno"],
+            Template::Short => &["Cvcn"],
+            Template::Basic => &["aaanaaan", "aannaaan", "aaannaaa"],
+            Template::Pin => &["nnnn"],
+            Template::Name => &["cvccvcvcv"],
+            Template::Phrase => &[
+                "cvcc cvc cvccvcv cvc",
+                "cvc cvccvcvcv cvcv",
+                "cv cvccv cvc cvcvccv",
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Template::Maximum => write!(f, "maximum"),
+            Template::Long => write!(f, "long"),
+            Template::Medium => write!(f, "medium"),
+            Template::Short => write!(f, "short"),
+            Template::Basic => write!(f, "basic"),
+            Template::Pin => write!(f, "pin"),
+            Template::Name => write!(f, "name"),
+            Template::Phrase => write!(f, "phrase"),
+        }
+    }
+}
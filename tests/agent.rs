@@ -0,0 +1,164 @@
+#![cfg(all(unix, feature = "cli"))]
+
+#[cfg(test)]
+mod test_agent {
+    use std::{
+        env, fs,
+        io::{BufRead, BufReader},
+        os::unix::fs::PermissionsExt,
+        path::PathBuf,
+        process::{Child, Command, Stdio},
+        thread,
+        time::Duration,
+    };
+
+    /// Empty home directory, so no configuration or site store is used.
+    fn home(name: &str) -> PathBuf {
+        let home = env::temp_dir().join(format!("rgp-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home).unwrap();
+        home
+    }
+
+    fn rgp(home: &PathBuf) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_rgp"));
+        command
+            .env_clear()
+            .env("HOME", home)
+            .env("XDG_CONFIG_HOME", home.join("config"));
+        command
+    }
+
+    /// Start an agent in the foreground, waiting until it listens.
+    fn start_agent(home: &PathBuf, lifetime: &str) -> (Child, PathBuf) {
+        let socket = home.join("agent").join("agent.sock");
+        let mut agent = rgp(home)
+            .args([
+                "agent",
+                "--foreground",
+                "-p",
+                "masterpassword",
+                "--lifetime",
+            ])
+            .arg(lifetime)
+            .arg("--socket")
+            .arg(&socket)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut exports = String::new();
+        BufReader::new(agent.stdout.take().unwrap())
+            .read_line(&mut exports)
+            .unwrap();
+        assert!(exports.starts_with("RGP_AGENT_SOCK="));
+        (agent, socket)
+    }
+
+    #[test]
+    fn serves_passwords_to_clients() {
+        let home = home("agent-serve");
+        let (mut agent, socket) = start_agent(&home, "60");
+
+        let mode = fs::metadata(socket.parent().unwrap())
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(0o700, mode & 0o777);
+
+        let output = rgp(&home)
+            .args(["-d", "https://www.example.com/foo"])
+            .env("RGP_AGENT_SOCK", &socket)
+            .stdin(Stdio::null())
+            .output()
+            .unwrap();
+        assert_eq!("jHMOHn7bRs\n", String::from_utf8_lossy(&output.stdout));
+
+        let output = rgp(&home)
+            .args(["fingerprint", "-H", "sha512"])
+            .env("RGP_AGENT_SOCK", &socket)
+            .stdin(Stdio::null())
+            .output()
+            .unwrap();
+        assert_eq!("f3:69:2c\n", String::from_utf8_lossy(&output.stdout));
+
+        let output = rgp(&home)
+            .args(["-d", "example.com", "-s", "secret", "-l", "24", "-r", "50"])
+            .env("RGP_AGENT_SOCK", &socket)
+            .stdin(Stdio::null())
+            .output()
+            .unwrap();
+        assert_eq!(
+            "izHhm22SMfZeg8Q3t2BrZgAA\n",
+            String::from_utf8_lossy(&output.stdout)
+        );
+
+        agent.kill().unwrap();
+        agent.wait().unwrap();
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn locks_memory_in_background() {
+        let home = home("agent-lock");
+        let socket = home.join("agent").join("agent.sock");
+        let output = rgp(&home)
+            .args(["agent", "-p", "masterpassword", "--lifetime", "60"])
+            .arg("--socket")
+            .arg(&socket)
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let pid = stdout
+            .lines()
+            .find_map(|line| line.strip_prefix("echo Agent pid "))
+            .and_then(|pid| pid.strip_suffix(';'))
+            .unwrap()
+            .to_string();
+        // The agent locks the master password once it's in the background.
+        let mut locked = 0;
+        for _ in 0..50 {
+            let status = fs::read_to_string(format!("/proc/{}/status", pid)).unwrap();
+            locked = status
+                .lines()
+                .find_map(|line| line.strip_prefix("VmLck:"))
+                .and_then(|kb| kb.trim().trim_end_matches("kB").trim().parse().ok())
+                .unwrap_or(0);
+            if locked > 0 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        Command::new("kill").arg(&pid).status().unwrap();
+        assert!(locked > 0);
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn exits_after_lifetime() {
+        let home = home("agent-lifetime");
+        let (mut agent, socket) = start_agent(&home, "1");
+        for _ in 0..50 {
+            if agent.try_wait().unwrap().is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        assert!(
+            agent
+                .try_wait()
+                .unwrap()
+                .is_some_and(|status| status.success())
+        );
+        assert!(!socket.exists());
+
+        let output = rgp(&home)
+            .args(["-d", "example.com"])
+            .env("RGP_AGENT_SOCK", &socket)
+            .stdin(Stdio::null())
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let _ = fs::remove_dir_all(&home);
+    }
+}
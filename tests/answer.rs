@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod test_answer {
+    use rustgenpass::answer::{AnswerError, entropy, generate};
+    use rustgenpass::{GenerateConfig, HashAlgorithm};
+
+    fn answer(domain: &str, length: usize, question: &str) -> String {
+        let config = GenerateConfig {
+            length,
+            ..GenerateConfig::default()
+        };
+        generate("masterpassword", domain, config, question)
+            .unwrap()
+            .password
+    }
+
+    // Vectors computed with an independent implementation in Node.js.
+    #[test]
+    fn derives_answers_from_final_hash_and_question() {
+        assert_eq!(
+            "gomolekili",
+            answer("example.com", 10, "Mother's maiden name")
+        );
+        assert_eq!("rutovinupi", answer("example.com", 10, "first pet"));
+        assert_eq!("sihu", answer("example.org", 4, "City of birth"));
+        let config = GenerateConfig {
+            length: 16,
+            hash_algorithm: HashAlgorithm::SHA512,
+            ..GenerateConfig::default()
+        };
+        assert_eq!(
+            "tarevulakogogugu",
+            generate("masterpassword", "example.com", config, "first pet")
+                .unwrap()
+                .password
+        );
+    }
+
+    #[test]
+    fn ignores_case_and_whitespace_of_questions() {
+        assert_eq!(
+            answer("example.com", 10, "Mother's maiden name"),
+            answer("example.com", 10, "  mother's\tMAIDEN   name ")
+        );
+        assert_ne!(
+            answer("example.com", 10, "mother's maiden name"),
+            answer("example.com", 10, "mothers maiden name")
+        );
+    }
+
+    #[test]
+    fn rejects_empty_questions() {
+        assert_eq!(
+            Err(AnswerError::EmptyQuestion),
+            generate(
+                "masterpassword",
+                "example.com",
+                GenerateConfig::default(),
+                " "
+            )
+            .map(|generated| generated.password)
+        );
+    }
+
+    #[test]
+    fn computes_entropy() {
+        assert_eq!(4.0 + 5_f64.log2(), entropy(2));
+        assert_eq!(3.0 * 4.0 + 2.0 * 5_f64.log2(), entropy(5));
+    }
+}
@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod test_charset {
+    use rustgenpass::charset::{CharsetError, entropy, generate};
+    use rustgenpass::{GenerateConfig, HashAlgorithm};
+
+    fn password(characters: &str, config: GenerateConfig) -> String {
+        generate("masterpassword", "example.com", config, characters)
+            .unwrap()
+            .password
+    }
+
+    fn length(length: usize) -> GenerateConfig {
+        GenerateConfig {
+            length,
+            ..GenerateConfig::default()
+        }
+    }
+
+    // Vectors computed with an independent implementation in Node.js.
+    #[test]
+    fn maps_final_hash_onto_characters() {
+        assert_eq!(
+            "38JJZYHBHY",
+            password("ABCDEFGHJKLMNPQRSTUVWXYZ23456789", length(10))
+        );
+        assert_eq!("@&!!*&*@*&@!", password("!@#$%^&*", length(12)));
+        let config = GenerateConfig {
+            length: 4,
+            hash_algorithm: HashAlgorithm::SHA512,
+            ..GenerateConfig::default()
+        };
+        assert_eq!("1100", password("01", config));
+    }
+
+    #[test]
+    fn applies_password_policy_to_classes_in_set() {
+        let generated = password(
+            "abcdefghijkmnpqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ23456789",
+            length(16),
+        );
+        assert_eq!("rJkCkLT2Nvun9rww", generated);
+        for length in 4..=24 {
+            let generated = password("aB1", self::length(length));
+            assert!(generated.starts_with('a'), "{}", generated);
+            assert!(generated.contains('B'), "{}", generated);
+            assert!(generated.contains('1'), "{}", generated);
+        }
+    }
+
+    #[test]
+    fn counts_repeated_characters_once() {
+        assert_eq!(
+            password("0123456789", length(8)),
+            password("01234567890123", length(8))
+        );
+    }
+
+    #[test]
+    fn estimates_entropy() {
+        // Logarithms of the passwords counted by brute force.
+        for (characters, length, bits) in [
+            ("aB1x", 4, 5.169925001442312),
+            ("0123456789abcdef", 5, 18.556146773125832),
+            ("ABC123", 5, 12.8317030992143),
+        ] {
+            assert!(
+                (bits - entropy(characters, length)).abs() < 1e-9,
+                "{}",
+                characters
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_sets() {
+        for characters in ["", "a", "aaaa"] {
+            assert_eq!(
+                Err(CharsetError::InsufficientCharacters),
+                generate("masterpassword", "example.com", length(10), characters)
+                    .map(|generated| generated.password)
+            );
+        }
+        let characters: String = ('\u{100}'..'\u{201}').collect();
+        assert_eq!(
+            Err(CharsetError::TooManyCharacters(257)),
+            generate("masterpassword", "example.com", length(10), &characters)
+                .map(|generated| generated.password)
+        );
+    }
+}
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod test_entropy {
+    use rustgenpass::{GenerateConfig, HashAlgorithm, entropy};
+
+    fn config(length: usize, hash_algorithm: HashAlgorithm) -> GenerateConfig {
+        GenerateConfig {
+            length,
+            hash_algorithm,
+            ..GenerateConfig::default()
+        }
+    }
+
+    #[test]
+    fn counts_remapped_base64_characters() {
+        // 60 characters once and `8` and `9` twice out of 64.
+        let entropy = entropy(&config(10, HashAlgorithm::MD5));
+        assert!((entropy.unconstrained_bits - 59.375).abs() < 1e-9);
+    }
+
+    #[test]
+    fn subtracts_rejected_passwords() {
+        let entropy = entropy(&config(10, HashAlgorithm::MD5));
+        assert!((entropy.acceptance_rate - 0.34).abs() < 0.001);
+        assert!(entropy.bits < entropy.unconstrained_bits);
+        assert!((entropy.extra_rounds() - 1.94).abs() < 0.01);
+    }
+
+    #[test]
+    fn short_passwords_are_rejected_more_often() {
+        assert!(
+            entropy(&config(4, HashAlgorithm::MD5)).acceptance_rate
+                < entropy(&config(24, HashAlgorithm::MD5)).acceptance_rate
+        );
+    }
+
+    #[test]
+    fn md5_padding_adds_no_entropy() {
+        let md5_22 = entropy(&config(22, HashAlgorithm::MD5));
+        let md5_24 = entropy(&config(24, HashAlgorithm::MD5));
+        // The padding only makes a few more hashes satisfy the policy.
+        assert_eq!(md5_22.unconstrained_bits, md5_24.unconstrained_bits);
+        assert!(md5_24.bits - md5_22.bits < 0.001);
+        assert!(md5_24.bits < 128.0);
+        assert!(entropy(&config(24, HashAlgorithm::SHA512)).bits > 140.0);
+    }
+
+    #[test]
+    fn same_for_algorithms_below_md5_length() {
+        assert_eq!(
+            entropy(&config(16, HashAlgorithm::MD5)).bits,
+            entropy(&config(16, HashAlgorithm::SHA512)).bits
+        );
+    }
+}
@@ -0,0 +1,29 @@
+#![cfg(feature = "cli")]
+
+#[cfg(test)]
+mod test_env {
+    use std::{env, process::Command};
+
+    fn hostname(keep_subdomains: &str) -> String {
+        let output = Command::new(env!("CARGO_BIN_EXE_rgp"))
+            .env_clear()
+            .env("HOME", env::temp_dir())
+            .env("XDG_CONFIG_HOME", env::temp_dir().join("rgp-test-env"))
+            .env("RGP_KEEP_SUBDOMAINS", keep_subdomains)
+            .args(["hostname", "https://www.example.com/"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", keep_subdomains);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn accepts_boolish_flags() {
+        for value in ["1", "yes", "on", "true"] {
+            assert_eq!("www.example.com", hostname(value), "{}", value);
+        }
+        for value in ["0", "no", "off", "false"] {
+            assert_eq!("example.com", hostname(value), "{}", value);
+        }
+    }
+}
@@ -0,0 +1,14 @@
+#[cfg(test)]
+mod test_fingerprint {
+    use rustgenpass::fingerprint;
+
+    #[test]
+    fn fingerprints_master_password() {
+        assert_eq!("f3:69:2c", fingerprint("masterpassword"));
+    }
+
+    #[test]
+    fn differs_for_typos() {
+        assert_ne!(fingerprint("masterpassword"), fingerprint("masterpasswrod"));
+    }
+}
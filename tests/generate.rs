@@ -183,3 +183,41 @@ mod test_generate_with_config {
         );
     }
 }
+
+#[cfg(test)]
+mod test_generate_with_details {
+    use rustgenpass::{GenerateConfig, generate_with_config, generate_with_details};
+
+    #[test]
+    fn generates_same_password_as_generate_with_config() {
+        let generated =
+            generate_with_details("masterpassword", "example.com", GenerateConfig::default());
+        assert_eq!(
+            generate_with_config("masterpassword", "example.com", GenerateConfig::default()),
+            generated.password
+        );
+        assert_eq!(10, generated.rounds);
+    }
+
+    #[test]
+    fn counts_extra_rounds_for_password_policy() {
+        let config = GenerateConfig {
+            hash_rounds: 1,
+            length: 4,
+            ..GenerateConfig::default()
+        };
+        let generated = generate_with_details("masterpassword", "example.com", config);
+        assert_eq!("xe4X", generated.password);
+        assert_eq!(19, generated.rounds);
+    }
+
+    #[test]
+    fn handles_maximum_hash_rounds() {
+        let config = GenerateConfig {
+            hash_rounds: u8::MAX,
+            ..GenerateConfig::default()
+        };
+        let generated = generate_with_details("masterpassword", "example.com", config);
+        assert!(generated.rounds >= 255);
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod test_lesspass {
+    use rustgenpass::lesspass::{LessPassError, Profile, generate};
+
+    fn profile(lowercase: bool, uppercase: bool, digits: bool, symbols: bool) -> Profile {
+        Profile {
+            lowercase,
+            uppercase,
+            digits,
+            symbols,
+            length: 12,
+            counter: 1,
+        }
+    }
+
+    // Vectors generated with lesspass-cli.
+    #[test]
+    fn matches_reference_character_sets() {
+        for (profile, expected) in [
+            (profile(false, false, false, true), ".;#+]<`.:.^["),
+            (profile(false, false, true, false), "565301097089"),
+            (profile(false, true, false, false), "LFOTFIGTSMDD"),
+            (profile(true, false, false, false), "lfotfigtsmdd"),
+            (profile(true, false, true, true), "\"0c6!6o;6p&9"),
+            (profile(true, true, true, true), "X%W(PxH#j7_2"),
+        ] {
+            assert_eq!(
+                expected,
+                generate("password", "example.org", "user@example.org", &profile).unwrap(),
+                "{:?}",
+                profile
+            );
+        }
+    }
+
+    #[test]
+    fn matches_reference_counter_and_length() {
+        let profile = Profile {
+            symbols: false,
+            counter: 8,
+            length: 12,
+            ..Profile::default()
+        };
+        assert_eq!(
+            "rZmMQiTwx8wy",
+            generate("password", "example.org", "user@example.org", &profile).unwrap()
+        );
+        let profile = Profile {
+            symbols: false,
+            length: 32,
+            ..Profile::default()
+        };
+        assert_eq!(
+            "BNGqCkw7MJmO3Pd1B0skLfZzZbEl989K",
+            generate("password", "example.org", "user@example.org", &profile).unwrap()
+        );
+    }
+
+    #[test]
+    fn matches_default_profile() {
+        assert_eq!(
+            "WHLpUL)e00[iHR+w",
+            generate(
+                "password",
+                "example.org",
+                "contact@example.org",
+                &Profile::default()
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_profiles() {
+        let profile = Profile {
+            length: 36,
+            ..Profile::default()
+        };
+        assert_eq!(
+            Err(LessPassError::InvalidLength(36)),
+            generate("password", "example.org", "", &profile)
+        );
+        let profile = Profile {
+            lowercase: false,
+            uppercase: false,
+            digits: false,
+            symbols: false,
+            ..Profile::default()
+        };
+        assert_eq!(
+            Err(LessPassError::NoCharacterSets),
+            generate("password", "example.org", "", &profile)
+        );
+    }
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "cli")]
+
+#[cfg(test)]
+mod test_native_host {
+    use serde_json::{Value, json};
+    use std::{
+        env, fs,
+        io::{Read, Write},
+        path::PathBuf,
+        process::{Child, ChildStdout, Command, Stdio},
+    };
+
+    /// Empty home directory, so no configuration or site store is used.
+    fn home(name: &str) -> PathBuf {
+        let home = env::temp_dir().join(format!("rgp-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home).unwrap();
+        home
+    }
+
+    fn rgp(home: &PathBuf) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_rgp"));
+        command
+            .env_clear()
+            .env("HOME", home)
+            .env("XDG_CONFIG_HOME", home.join("config"))
+            .env("XDG_RUNTIME_DIR", home.join("run"));
+        command
+    }
+
+    /// Start the host like Firefox does, with the manifest path and the
+    /// extension id as arguments.
+    fn start_host(home: &PathBuf) -> Child {
+        rgp(home)
+            .args(["native-host", "/tmp/rustgenpass.json", "rgp@example.com"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap()
+    }
+
+    fn send(host: &mut Child, message: &Value) {
+        let message = serde_json::to_vec(message).unwrap();
+        let stdin = host.stdin.as_mut().unwrap();
+        stdin
+            .write_all(&(message.len() as u32).to_ne_bytes())
+            .unwrap();
+        stdin.write_all(&message).unwrap();
+        stdin.flush().unwrap();
+    }
+
+    fn receive(stdout: &mut ChildStdout) -> Value {
+        let mut length = [0; 4];
+        stdout.read_exact(&mut length).unwrap();
+        let mut message = vec![0; u32::from_ne_bytes(length) as usize];
+        stdout.read_exact(&mut message).unwrap();
+        serde_json::from_slice(&message).unwrap()
+    }
+
+    #[test]
+    fn answers_requests_over_pipes() {
+        let home = home("native-host");
+        let mut host = start_host(&home);
+        let mut stdout = host.stdout.take().unwrap();
+
+        send(
+            &mut host,
+            &json!({
+                "url": "https://www.example.com/foo/bar.html",
+                "password": "masterpassword",
+            }),
+        );
+        let response = receive(&mut stdout);
+        assert_eq!(json!("example.com"), response["domain"]);
+        assert_eq!(json!("jHMOHn7bRs"), response["password"]);
+
+        send(
+            &mut host,
+            &json!({
+                "url": "https://www.example.com/",
+                "password": "masterpassword",
+                "secret": "secret",
+                "length": 24,
+                "rounds": "50",
+            }),
+        );
+        let response = receive(&mut stdout);
+        assert_eq!(json!("izHhm22SMfZeg8Q3t2BrZgAA"), response["password"]);
+        assert_eq!(json!(24), response["length"]);
+
+        send(
+            &mut host,
+            &json!({ "url": "https://www.example.com/", "password": "masterpassword", "length": 40 }),
+        );
+        assert_eq!(
+            json!("Invalid value for length: 40"),
+            receive(&mut stdout)["error"]
+        );
+
+        send(&mut host, &json!({ "url": "https://www.example.com/" }));
+        assert!(receive(&mut stdout)["error"].is_string());
+
+        drop(host.stdin.take());
+        assert!(host.wait().unwrap().success());
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn prints_manifest() {
+        let home = home("native-manifest");
+        let output = rgp(&home)
+            .args([
+                "native-host",
+                "manifest",
+                "--browser",
+                "chrome",
+                "--path",
+                "/usr/local/bin/rgp-native-host",
+                "--extension-id",
+                "abcdef",
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let manifest: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(json!("rustgenpass"), manifest["name"]);
+        assert_eq!(
+            json!(["chrome-extension://abcdef/"]),
+            manifest["allowed_origins"]
+        );
+        let _ = fs::remove_dir_all(&home);
+    }
+}
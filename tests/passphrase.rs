@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod test_passphrase {
+    use rustgenpass::passphrase::{
+        Capitalization, PassphraseConfig, PassphraseError, entropy, generate,
+    };
+    use rustgenpass::{GenerateConfig, HashAlgorithm, generate_with_details};
+
+    fn passphrase(config: GenerateConfig, passphrase: &PassphraseConfig) -> String {
+        generate("masterpassword", "example.com", config, passphrase)
+            .unwrap()
+            .password
+    }
+
+    // Vectors computed with an independent implementation using BigInt in
+    // Node.js.
+    #[test]
+    fn maps_final_hash_to_words() {
+        assert_eq!(
+            "freebie-destiny-saga-strung-triage-runner",
+            passphrase(GenerateConfig::default(), &PassphraseConfig::default())
+        );
+        let config = PassphraseConfig {
+            words: 4,
+            separator: " ".to_string(),
+            digit: true,
+            ..PassphraseConfig::default()
+        };
+        assert_eq!(
+            "freebie destiny saga strung0",
+            passphrase(GenerateConfig::default(), &config)
+        );
+        let config = GenerateConfig {
+            length: 16,
+            hash_algorithm: HashAlgorithm::SHA512,
+            ..GenerateConfig::default()
+        };
+        let words = PassphraseConfig {
+            words: 8,
+            separator: ".".to_string(),
+            digit: true,
+            ..PassphraseConfig::default()
+        };
+        assert_eq!(
+            "lens.kiwi.escargot.punctual2.jubilant.coasting.dollar.pulsate",
+            passphrase(config, &words)
+        );
+    }
+
+    #[test]
+    fn capitalizes_words() {
+        let capitalized = |capitalization| {
+            let config = PassphraseConfig {
+                words: 3,
+                capitalization,
+                ..PassphraseConfig::default()
+            };
+            passphrase(GenerateConfig::default(), &config)
+        };
+        assert_eq!("freebie-destiny-saga", capitalized(Capitalization::Lower));
+        assert_eq!("Freebie-Destiny-Saga", capitalized(Capitalization::Title));
+        assert_eq!("FREEBIE-DESTINY-SAGA", capitalized(Capitalization::Upper));
+    }
+
+    #[test]
+    fn reports_rounds_of_password() {
+        let generated = generate(
+            "masterpassword",
+            "example.com",
+            GenerateConfig::default(),
+            &PassphraseConfig::default(),
+        )
+        .unwrap();
+        let password =
+            generate_with_details("masterpassword", "example.com", GenerateConfig::default());
+        assert_eq!(password.rounds, generated.rounds);
+    }
+
+    #[test]
+    fn rejects_invalid_word_counts() {
+        for words in [0, 2, 21] {
+            let config = PassphraseConfig {
+                words,
+                ..PassphraseConfig::default()
+            };
+            assert_eq!(
+                Err(PassphraseError::InvalidWordCount(words)),
+                generate(
+                    "masterpassword",
+                    "example.com",
+                    GenerateConfig::default(),
+                    &config
+                )
+                .map(|generated| generated.password)
+            );
+        }
+    }
+
+    #[test]
+    fn estimates_entropy() {
+        let config = PassphraseConfig {
+            words: 4,
+            digit: true,
+            ..PassphraseConfig::default()
+        };
+        let bits = 4.0 * 7776_f64.log2() + 40_f64.log2();
+        assert!((bits - entropy(&config)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,239 @@
+#[cfg(test)]
+mod test_passwordmaker {
+    use rustgenpass::HostnameConfig;
+    use rustgenpass::passwordmaker::{
+        Algorithm, DEFAULT_CHARACTERS, LeetMode, PasswordMakerError, Profile, UrlComponents,
+        generate, generate_with_text,
+    };
+
+    const ALPHANUMERIC: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    const PASSWORD: &str = "0123456789012345678901234567890123456789012345678901234567890123456789012345678901234567890123456789";
+    const TEXT: &str = ".0123456789012345678901234567890123456789012345678901234567890123456789012345678901234567890123456789öä@€Whatever";
+
+    fn profile(algorithm: Algorithm) -> Profile {
+        Profile {
+            algorithm,
+            leet: LeetMode::BeforeHashing,
+            leet_level: 9,
+            characters: ALPHANUMERIC.to_string(),
+            username: "max_mustermann".to_string(),
+            modifier: "modification".to_string(),
+            length: 64,
+            prefix: "pre".to_string(),
+            suffix: "suf".to_string(),
+            ..Profile::default()
+        }
+    }
+
+    // Vectors from the tests of passwordmaker-rs, checked against
+    // PasswordMaker Pro.
+    #[test]
+    fn matches_reference_algorithms() {
+        for (algorithm, expected) in [
+            (
+                Algorithm::Md4,
+                "preBhaY7RkI3FU2Dd1gGbkHGXHcaS9Rla5yOyBsxtRhYjUV4CoEKST1N73Ipmsuf",
+            ),
+            (
+                Algorithm::HmacMd4,
+                "preCWxYmOtw9SouPQCHRRxLWODlFQ5LFitOpYMLHdnELniLHtQpdH5U2eOAOHsuf",
+            ),
+            (
+                Algorithm::Md5,
+                "preDPeKYAEZMwmB99f7i48vWApmx8ZLbz46s2nyG6KNO00G4nEElILxWAtGLGsuf",
+            ),
+            (
+                Algorithm::HmacMd5,
+                "preGWR5UvFWn69uJQqedOi07JErUQfxJLLB3ZlLPjltwUI0HFDoN6p5xhGdd5suf",
+            ),
+            (
+                Algorithm::Md5V6,
+                "pred554290760c59fd928b7aae270c31fdbb8857442f34d92cdaca38fcfc0suf",
+            ),
+            (
+                Algorithm::HmacMd5V6,
+                "pread606e84133024f01831a2ce2f6728654bca7c4dd8098ce2e6f75693d2suf",
+            ),
+            (
+                Algorithm::Sha1,
+                "preWiv8G0J7zvTurM7Mwjy7LeXKBfbJCqJtP6EOAR8dhgF8dFh6h3OCUybzwusuf",
+            ),
+            (
+                Algorithm::HmacSha1,
+                "prekLwEUc8ccgo6cK6nct1E58HZu3x5q0yCN8HHLVMx0QzjKMAfHCMyGeZxFEsuf",
+            ),
+            (
+                Algorithm::Sha256,
+                "pregtXH0tXq1nKXH6adaYb9dtXgCAFl2cqCPMQW3E7EeDggB5Oft4HaNdq5uRsuf",
+            ),
+            (
+                Algorithm::HmacSha256,
+                "pre5oyv5RXFzY0NiZF4b5JWQj5RUtotkI5dbJOeRJmSjpiYllu5ZZ8FXZqyY4suf",
+            ),
+            (
+                Algorithm::Rmd160,
+                "preFJeYiiAXx8Aa1Fhvyy0ffw7D9AMU2qKLg5BBjwZxyT6rsbHctS1Yv1PhGjsuf",
+            ),
+            (
+                Algorithm::HmacRmd160,
+                "preZ1zVB4UtRfvu6PhBvMPTkmAbX9WZ6Xzqb20OKFmKrFMfyF2eB4ImF2fhmWsuf",
+            ),
+        ] {
+            assert_eq!(
+                expected,
+                generate_with_text(PASSWORD, TEXT, &profile(algorithm.clone())).unwrap(),
+                "{}",
+                algorithm
+            );
+        }
+    }
+
+    #[test]
+    fn matches_reference_defaults() {
+        let password = generate_with_text("1", ".abcdefghij", &Profile::default());
+        assert_eq!("J3>'1F\"/", password.unwrap());
+    }
+
+    #[test]
+    fn keeps_leading_zeros_of_version_06() {
+        let profile = |algorithm| Profile {
+            algorithm,
+            characters: "0123456789abcdef".to_string(),
+            ..Profile::default()
+        };
+        let v6 = generate_with_text("a", "01", &profile(Algorithm::Md5V6));
+        assert_eq!("00d2a735", v6.unwrap());
+        let modern = generate_with_text("a", "01", &profile(Algorithm::Md5));
+        assert_eq!("d2a73551", modern.unwrap());
+    }
+
+    #[test]
+    fn hashes_low_bytes_in_version_06() {
+        let profile = Profile {
+            algorithm: Algorithm::HmacMd5V6,
+            length: 47,
+            ..Profile::default()
+        };
+        assert_eq!(
+            "28e1392052364d34c7e42e2711ccdd62c67a0a30dbf568a",
+            generate_with_text("password", "€äß", &profile).unwrap()
+        );
+    }
+
+    #[test]
+    fn applies_leet_after_hashing_to_each_part() {
+        let profile = Profile {
+            algorithm: Algorithm::Md4,
+            leet: LeetMode::AfterHashing,
+            characters: "ΣΔΠΖ".to_string(),
+            length: 64,
+            ..Profile::default()
+        };
+        // Mind the final sigma at the end of the first part.
+        assert_eq!(
+            "ζδζσσπσζδδσδπζδδδπσπζπζδδζζππσζσσζδπδσζπζππδσπσζζπσζσδπζσζπδσςπδ",
+            generate_with_text("password", "123456", &profile).unwrap()
+        );
+    }
+
+    #[test]
+    fn truncates_suffix() {
+        let profile = Profile {
+            length: 5,
+            ..profile(Algorithm::HmacRmd160)
+        };
+        let suffix = Profile {
+            suffix: "suffix".to_string(),
+            ..profile.clone()
+        };
+        assert_eq!(
+            "suffi",
+            generate_with_text(PASSWORD, TEXT, &suffix).unwrap()
+        );
+        let both = Profile {
+            leet: LeetMode::Both,
+            ..suffix
+        };
+        assert_eq!("suffi", generate_with_text(PASSWORD, TEXT, &both).unwrap());
+    }
+
+    #[test]
+    fn selects_url_components() {
+        let url = "https://user@login.www.example.com:8443/path?query#fragment";
+        let text = |protocol, subdomains, domain, path| {
+            UrlComponents {
+                protocol,
+                subdomains,
+                domain,
+                path,
+            }
+            .text_to_use(url)
+        };
+        assert_eq!("example.com", text(false, false, true, false));
+        assert_eq!("login.www.example.com", text(false, true, true, false));
+        assert_eq!("https://example.com", text(true, false, true, false));
+        assert_eq!(
+            "example.com:8443/path?query#fragment",
+            text(false, false, true, true)
+        );
+        assert_eq!(
+            "https://login.www:8443/path?query#fragment",
+            text(true, true, false, true)
+        );
+        assert_eq!(
+            "example.com",
+            UrlComponents::default().text_to_use("example.com")
+        );
+    }
+
+    #[test]
+    fn maps_hostname_config() {
+        let url = "https://www.example.com/path";
+        let components = |passthrough, keep_subdomains| {
+            UrlComponents::from(&HostnameConfig {
+                passthrough,
+                keep_subdomains,
+            })
+            .text_to_use(url)
+        };
+        assert_eq!("example.com", components(false, false));
+        assert_eq!("www.example.com", components(false, true));
+        assert_eq!(url, components(true, false));
+        assert_eq!(
+            generate_with_text("password", "example.com", &Profile::default()),
+            generate("password", url, &Profile::default())
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_profiles() {
+        let characters = Profile {
+            characters: "a".to_string(),
+            ..Profile::default()
+        };
+        assert_eq!(
+            Err(PasswordMakerError::InsufficientCharacters),
+            generate("password", "example.com", &characters)
+        );
+        let level = Profile {
+            leet_level: 10,
+            ..Profile::default()
+        };
+        assert_eq!(
+            Err(PasswordMakerError::InvalidLeetLevel(10)),
+            generate("password", "example.com", &level)
+        );
+        let components = Profile {
+            url_components: UrlComponents {
+                domain: false,
+                ..UrlComponents::default()
+            },
+            ..Profile::default()
+        };
+        assert_eq!(
+            Err(PasswordMakerError::EmptyText),
+            generate("password", "example.com", &components)
+        );
+        assert_eq!(94, DEFAULT_CHARACTERS.chars().count());
+    }
+}
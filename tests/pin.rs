@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod test_pin {
+    use rustgenpass::pin::{PinError, entropy, generate, is_weak};
+    use rustgenpass::{GenerateConfig, HashAlgorithm};
+
+    fn pin(domain: &str, length: usize, reject_weak: bool) -> String {
+        let config = GenerateConfig {
+            length,
+            ..GenerateConfig::default()
+        };
+        generate("masterpassword", domain, config, reject_weak)
+            .unwrap()
+            .password
+    }
+
+    // Vectors computed with an independent implementation in Node.js.
+    #[test]
+    fn derives_digits_from_final_hash() {
+        assert_eq!("3793", pin("example.com", 4, false));
+        assert_eq!("379371", pin("example.com", 6, false));
+        assert_eq!("7652", pin("example.org", 4, false));
+        let config = GenerateConfig {
+            length: 8,
+            hash_algorithm: HashAlgorithm::SHA512,
+            ..GenerateConfig::default()
+        };
+        assert_eq!(
+            "93089366",
+            generate("masterpassword", "example.com", config, false)
+                .unwrap()
+                .password
+        );
+    }
+
+    #[test]
+    fn rejects_weak_pins() {
+        // 1012 looks like a date and 4747 repeats a pattern.
+        assert_eq!("1012", pin("site179.com", 4, false));
+        assert_eq!("8480", pin("site179.com", 4, true));
+        assert_eq!("3793", pin("example.com", 4, true));
+    }
+
+    #[test]
+    fn detects_weak_pins() {
+        for weak in [
+            "0000", "1212", "12121", "123123", "1234", "8765", "8901", "43210", "2512", "1225",
+            "2902", "1987", "2024", "251299", "122599", "991225", "25121999", "12252001",
+            "20011225",
+        ] {
+            assert!(is_weak(weak), "{}", weak);
+        }
+        for strong in [
+            "3793", "8480", "3002", "1899", "2100", "12354", "379371", "25131999", "25121899",
+            "93089366",
+        ] {
+            assert!(!is_weak(strong), "{}", strong);
+        }
+    }
+
+    #[test]
+    fn ignores_non_digits() {
+        for input in ["a0", "0a", "12 4", "１２３４", "-123"] {
+            assert!(!is_weak(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn estimates_entropy() {
+        assert!((entropy(6, false) - 1e6_f64.log2()).abs() < 1e-9);
+        assert!(entropy(8, true) < entropy(8, false));
+        assert!(entropy(5, true) < entropy(5, false));
+    }
+
+    #[test]
+    fn rejects_invalid_lengths() {
+        for length in [3, 9, 10] {
+            let config = GenerateConfig {
+                length,
+                ..GenerateConfig::default()
+            };
+            assert_eq!(
+                Err(PinError::InvalidLength(length)),
+                generate("masterpassword", "example.com", config, false)
+                    .map(|generated| generated.password)
+            );
+        }
+    }
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod test_pwdhash {
+    use rustgenpass::{get_hostname, pwdhash::generate};
+
+    /// Passwords computed with a port of the JavaScript of the PwdHash
+    /// extension. They aren't published test vectors, none were available,
+    /// so a mistake made by both ports goes unnoticed.
+    const VECTORS: [(&str, &str, &str); 6] = [
+        ("masterpassword", "example.com", "VNPpJ4Wmq2r5t2XH"),
+        ("password", "example.com", "4QAIn8SvaW"),
+        ("hunter2", "github.com", "n1fGXufpJ"),
+        ("1234", "bank.com", "V4hRBv"),
+        ("secret!", "example.com", "XdZL3cfM/"),
+        ("Pa$$w0rd", "www.example.co.uk", "ScqB+o5gkt"),
+    ];
+
+    #[test]
+    fn matches_port_of_extension() {
+        for (password, domain, expected) in VECTORS {
+            assert_eq!(expected, generate(password, domain), "{}", password);
+        }
+    }
+
+    #[test]
+    fn is_as_long_as_password_with_prefix() {
+        assert_eq!(16, generate("masterpassword", "example.com").len());
+        assert_eq!(4, generate("", "example.com").len());
+        assert_eq!(4, generate("a", "example.com").len());
+    }
+
+    #[test]
+    fn has_symbols_only_if_password_has() {
+        assert!(
+            generate("masterpassword", "example.com")
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric())
+        );
+        assert!(
+            generate("secret!", "example.com")
+                .chars()
+                .any(|c| !c.is_ascii_alphanumeric())
+        );
+    }
+
+    #[test]
+    fn pads_long_passwords_like_extension() {
+        // The hash runs out after 22 characters, and the extension pads the
+        // password with NUL characters, replaced with letters when the
+        // master password has no symbols.
+        assert_eq!(
+            "AcX4tLKDjzfMJG3FEAgjfwAAAA",
+            generate("abcdefghijklmnopqrstuvwxyz0123", "google.com")
+        );
+        assert_eq!(
+            "NmnoiPi+R1P74SlzhhGmNQ\0\0\0\0",
+            generate("correct horse battery staple", "example.com")
+        );
+    }
+
+    #[test]
+    fn uses_isolated_domain() {
+        let domain = get_hostname("https://www.example.com/foo/bar.html").unwrap();
+        assert_eq!("VNPpJ4Wmq2r5t2XH", generate("masterpassword", &domain));
+    }
+}
@@ -0,0 +1,260 @@
+#[cfg(test)]
+mod test_scheme {
+    use rustgenpass::scheme::{Params, Registry, Scheme, SchemeError};
+    use rustgenpass::{
+        GenerateConfig, GeneratedPassword, HashAlgorithm, answer, charset, generate_with_details,
+        lesspass, passphrase, passwordmaker, pin, pwdhash, spectre,
+    };
+
+    fn params(params: &[(&str, &str)]) -> Params {
+        params
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn generate(scheme: &str, site: &str, with: &[(&str, &str)]) -> Result<String, SchemeError> {
+        let registry = Registry::default();
+        let generated = registry
+            .get(scheme)?
+            .generate("masterpassword", site, &params(with))?;
+        Ok(generated.password)
+    }
+
+    #[test]
+    fn registers_schemes() {
+        assert_eq!(
+            vec![
+                "supergenpass",
+                "pwdhash",
+                "spectre",
+                "lesspass",
+                "passwordmaker"
+            ],
+            Registry::default().names().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(SchemeError::UnknownScheme("unknown".to_string())),
+            Registry::default().get("unknown").err()
+        );
+    }
+
+    #[test]
+    fn matches_schemes() {
+        let config = GenerateConfig {
+            secret: Some("secret".to_string()),
+            length: 16,
+            hash_rounds: 20,
+            hash_algorithm: HashAlgorithm::SHA512,
+        };
+        let supergenpass = [
+            ("secret", "secret"),
+            ("length", "16"),
+            ("rounds", "20"),
+            ("hash", "sha512"),
+        ];
+        assert_eq!(
+            Ok(generate_with_details("masterpassword", "example.com", config).password),
+            generate("supergenpass", "example.com", &supergenpass)
+        );
+        assert_eq!(
+            Ok("Freebie Destiny Saga Strung0".to_string()),
+            generate(
+                "supergenpass",
+                "example.com",
+                &[
+                    ("format", "passphrase"),
+                    ("words", "4"),
+                    ("separator", " "),
+                    ("capitalize", "title"),
+                    ("digit", "true")
+                ]
+            )
+        );
+        assert_eq!(
+            Ok("8480".to_string()),
+            generate(
+                "supergenpass",
+                "site179.com",
+                &[("format", "pin"), ("reject-weak", "true")]
+            )
+        );
+        assert_eq!(
+            Ok("38JJZYHBHY".to_string()),
+            generate(
+                "supergenpass",
+                "example.com",
+                &[("charset", "ABCDEFGHJKLMNPQRSTUVWXYZ23456789")]
+            )
+        );
+        assert_eq!(
+            Ok("gomolekili".to_string()),
+            generate(
+                "supergenpass",
+                "example.com",
+                &[("format", "answer"), ("question", "Mother's maiden name")]
+            )
+        );
+        assert_eq!(
+            Ok(pwdhash::generate("masterpassword", "example.com")),
+            generate("pwdhash", "example.com", &[])
+        );
+        assert_eq!(
+            Ok(spectre::generate(
+                "Robert Lee Mitchell",
+                "masterpassword",
+                "example.com",
+                3,
+                &spectre::Template::Basic
+            )),
+            generate(
+                "spectre",
+                "example.com",
+                &[
+                    ("full-name", "Robert Lee Mitchell"),
+                    ("counter", "3"),
+                    ("template", "basic")
+                ]
+            )
+        );
+        let profile = lesspass::Profile {
+            symbols: false,
+            length: 20,
+            ..lesspass::Profile::default()
+        };
+        assert_eq!(
+            Ok(lesspass::generate("masterpassword", "example.com", "login", &profile).unwrap()),
+            generate(
+                "lesspass",
+                "example.com",
+                &[("login", "login"), ("length", "20"), ("no-symbols", "true")]
+            )
+        );
+        let profile = passwordmaker::Profile {
+            algorithm: passwordmaker::Algorithm::HmacSha256,
+            username: "login".to_string(),
+            length: 12,
+            ..passwordmaker::Profile::default()
+        };
+        assert_eq!(
+            Ok(passwordmaker::generate("masterpassword", "example.com", &profile).unwrap()),
+            generate(
+                "passwordmaker",
+                "example.com",
+                &[
+                    ("algorithm", "hmac-sha256"),
+                    ("login", "login"),
+                    ("length", "12")
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_params() {
+        assert_eq!(
+            Err(SchemeError::UnknownParam("pwdhash", "length".to_string())),
+            generate("pwdhash", "example.com", &[("length", "10")])
+        );
+        assert_eq!(
+            Err(SchemeError::MissingParam("spectre", "full-name")),
+            generate("spectre", "example.com", &[])
+        );
+        assert_eq!(
+            Err(SchemeError::InvalidParam("length", "40".to_string())),
+            generate("supergenpass", "example.com", &[("length", "40")])
+        );
+        assert_eq!(
+            Err(SchemeError::InvalidParam("hash", "sha1".to_string())),
+            generate("supergenpass", "example.com", &[("hash", "sha1")])
+        );
+        assert_eq!(
+            Err(SchemeError::Passphrase(
+                passphrase::PassphraseError::InvalidWordCount(2)
+            )),
+            generate(
+                "supergenpass",
+                "example.com",
+                &[("format", "passphrase"), ("words", "2")]
+            )
+        );
+        assert_eq!(
+            Err(SchemeError::Pin(pin::PinError::InvalidLength(10))),
+            generate(
+                "supergenpass",
+                "example.com",
+                &[("format", "pin"), ("pin-length", "10")]
+            )
+        );
+        assert_eq!(
+            Err(SchemeError::Charset(
+                charset::CharsetError::InsufficientCharacters
+            )),
+            generate("supergenpass", "example.com", &[("charset", "aaa")])
+        );
+        assert_eq!(
+            Err(SchemeError::MissingParam("supergenpass", "question")),
+            generate("supergenpass", "example.com", &[("format", "answer")])
+        );
+        assert_eq!(
+            Err(SchemeError::Answer(answer::AnswerError::EmptyQuestion)),
+            generate(
+                "supergenpass",
+                "example.com",
+                &[("format", "answer"), ("question", "")]
+            )
+        );
+        assert_eq!(
+            Err(SchemeError::LessPass(
+                lesspass::LessPassError::NoCharacterSets
+            )),
+            generate(
+                "lesspass",
+                "example.com",
+                &[
+                    ("no-lowercase", "true"),
+                    ("no-uppercase", "true"),
+                    ("no-digits", "true"),
+                    ("no-symbols", "true")
+                ]
+            )
+        );
+    }
+
+    struct Reversed;
+
+    impl Scheme for Reversed {
+        fn name(&self) -> &'static str {
+            "pwdhash"
+        }
+
+        fn params(&self) -> &'static [&'static str] {
+            &[]
+        }
+
+        fn generate(
+            &self,
+            password: &str,
+            site: &str,
+            _params: &Params,
+        ) -> Result<GeneratedPassword, SchemeError> {
+            Ok(GeneratedPassword {
+                password: format!("{}{}", password, site).chars().rev().collect(),
+                rounds: 1,
+            })
+        }
+    }
+
+    #[test]
+    fn replaces_registered_schemes() {
+        let mut registry = Registry::default();
+        registry.register(Box::new(Reversed));
+        assert_eq!(5, registry.names().count());
+        let generated = registry
+            .get("pwdhash")
+            .unwrap()
+            .generate("password", "example.com", &Params::new())
+            .unwrap();
+        assert_eq!("moc.elpmaxedrowssap", generated.password);
+    }
+}
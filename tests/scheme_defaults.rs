@@ -0,0 +1,55 @@
+#![cfg(feature = "cli")]
+
+#[cfg(test)]
+mod test_scheme_defaults {
+    use std::{env, process::Command};
+
+    fn rgp(vars: &[(&str, &str)], args: &[&str]) -> String {
+        let output = Command::new(env!("CARGO_BIN_EXE_rgp"))
+            .env_clear()
+            .env("HOME", env::temp_dir())
+            .env(
+                "XDG_CONFIG_HOME",
+                env::temp_dir().join("rgp-test-scheme-defaults"),
+            )
+            .envs(vars.iter().copied())
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", args);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    // Default profile of lesspass-cli, 16 characters.
+    #[test]
+    fn uses_default_length_of_lesspass() {
+        let args = [
+            "-p",
+            "password",
+            "--scheme",
+            "lesspass",
+            "--login",
+            "contact@example.org",
+            "-d",
+            "example.org",
+        ];
+        assert_eq!("WHLpUL)e00[iHR+w", rgp(&[], &args));
+        assert_eq!(12, rgp(&[], &[&args[..], &["-l", "12"]].concat()).len());
+        assert_eq!(12, rgp(&[("RGP_LENGTH", "12")], &args).len());
+    }
+
+    // Default profile of PasswordMaker, 8 characters.
+    #[test]
+    fn uses_default_length_of_passwordmaker() {
+        let args = [
+            "-p",
+            "masterpassword",
+            "--scheme",
+            "passwordmaker",
+            "-d",
+            "example.com",
+        ];
+        assert_eq!("ZAy&DE<X", rgp(&[], &args));
+        assert_eq!(12, rgp(&[], &[&args[..], &["-l", "12"]].concat()).len());
+    }
+}
@@ -0,0 +1,174 @@
+#![cfg(feature = "cli")]
+
+#[cfg(test)]
+mod test_serve {
+    use serde_json::{Value, json};
+    use std::{
+        env, fs,
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpStream,
+        path::PathBuf,
+        process::{Child, Command, Stdio},
+    };
+
+    const TOKEN: &str = "0123456789abcdef";
+
+    /// Empty home directory, so no configuration or site store is used.
+    fn home(name: &str) -> PathBuf {
+        let home = env::temp_dir().join(format!("rgp-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home).unwrap();
+        home
+    }
+
+    /// Start the server, returning where it listens.
+    fn start_server(home: &PathBuf, args: &[&str]) -> (Child, String) {
+        let mut server = Command::new(env!("CARGO_BIN_EXE_rgp"))
+            .env_clear()
+            .env("HOME", home)
+            .env("XDG_CONFIG_HOME", home.join("config"))
+            .env("RGP_SERVE_TOKEN", TOKEN)
+            .args(["serve", "-p", "masterpassword"])
+            .args(args)
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(server.stderr.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let address = line
+            .trim()
+            .strip_prefix("Listening on ")
+            .unwrap_or_else(|| panic!("Unexpected output: {}", line))
+            .to_string();
+        (server, address)
+    }
+
+    /// Send a request, returning the status code and the JSON body.
+    fn request<S: Read + Write>(
+        mut stream: S,
+        method: &str,
+        path: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> (u16, Value) {
+        let body = body.map(|body| body.to_string()).unwrap_or_default();
+        let authorization = token
+            .map(|token| format!("Authorization: Bearer {}\r\n", token))
+            .unwrap_or_default();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            authorization,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn serves_api_on_ephemeral_port() {
+        let home = home("serve-tcp");
+        let (mut server, address) = start_server(&home, &["--port", "0"]);
+        let address = address.strip_prefix("http://").unwrap().to_string();
+        assert!(address.starts_with("127.0.0.1:"));
+        let connect = || TcpStream::connect(&address).unwrap();
+
+        assert_eq!(
+            (200, json!({ "status": "ok" })),
+            request(connect(), "GET", "/health", None, None)
+        );
+
+        let (status, response) = request(
+            connect(),
+            "POST",
+            "/generate",
+            Some(TOKEN),
+            Some(json!({ "url": "https://www.example.com/foo/bar.html" })),
+        );
+        assert_eq!(200, status);
+        assert_eq!(json!("example.com"), response["domain"]);
+        assert_eq!(json!("jHMOHn7bRs"), response["password"]);
+
+        let (_, response) = request(
+            connect(),
+            "POST",
+            "/generate",
+            Some(TOKEN),
+            Some(json!({
+                "url": "example.com",
+                "secret": "secret",
+                "length": 24,
+                "rounds": 50,
+            })),
+        );
+        assert_eq!(json!("izHhm22SMfZeg8Q3t2BrZgAA"), response["password"]);
+
+        assert_eq!(
+            (200, json!({ "domain": "www.example.com" })),
+            request(
+                connect(),
+                "POST",
+                "/hostname",
+                Some(TOKEN),
+                Some(json!({ "url": "https://www.example.com/", "keep-subdomains": true })),
+            )
+        );
+
+        let (status, _) = request(
+            connect(),
+            "POST",
+            "/generate",
+            None,
+            Some(json!({ "url": "example.com" })),
+        );
+        assert_eq!(401, status);
+
+        let (status, response) = request(
+            connect(),
+            "POST",
+            "/generate",
+            Some(TOKEN),
+            Some(json!({ "url": "example.com", "length": 40 })),
+        );
+        assert_eq!(400, status);
+        assert_eq!(json!("Invalid value for length: 40"), response["error"]);
+
+        server.kill().unwrap();
+        server.wait().unwrap();
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn serves_api_on_unix_socket() {
+        use std::os::unix::net::UnixStream;
+
+        let home = home("serve-unix");
+        let socket = home.join("run").join("rgp.sock");
+        let (mut server, address) = start_server(&home, &["--socket", socket.to_str().unwrap()]);
+        assert_eq!(socket.to_str().unwrap(), address);
+
+        let (status, response) = request(
+            UnixStream::connect(&socket).unwrap(),
+            "POST",
+            "/generate",
+            Some(TOKEN),
+            Some(json!({ "url": "example.com" })),
+        );
+        assert_eq!(200, status);
+        assert_eq!(json!("jHMOHn7bRs"), response["password"]);
+
+        server.kill().unwrap();
+        server.wait().unwrap();
+        let _ = fs::remove_dir_all(&home);
+    }
+}
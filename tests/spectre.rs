@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod test_spectre {
+    use rustgenpass::spectre::{MasterKey, Template, generate};
+
+    // Vectors from the tests of the Master Password reference implementation.
+    #[test]
+    fn matches_reference_templates() {
+        let key = MasterKey::new("Robert Lee Mitchell", "banana colored duckling");
+        for (template, expected) in [
+            (Template::Maximum, "W6@692^B1#&@gVdSdLZ@"),
+            (Template::Long, "Jejr5[RepuSosp"),
+            (Template::Medium, "Jej2$Quv"),
+            (Template::Short, "Jej2"),
+            (Template::Basic, "WAo2xIg6"),
+            (Template::Pin, "7662"),
+            (Template::Name, "jejraquvo"),
+            (Template::Phrase, "jejr quv cabsibu tam"),
+        ] {
+            assert_eq!(
+                expected,
+                key.site_password("masterpasswordapp.com", 1, &template),
+                "{}",
+                template
+            );
+        }
+    }
+
+    #[test]
+    fn matches_reference_counters_and_unicode() {
+        let key = MasterKey::new("Robert Lee Mitchell", "banana colored duckling");
+        assert_eq!(
+            "XambHoqo6[Peni",
+            key.site_password("masterpasswordapp.com", u32::MAX, &Template::Long)
+        );
+        assert_eq!(
+            "LiheCuwhSerz6)",
+            key.site_password("⛄", 1, &Template::Long)
+        );
+        assert_eq!(
+            "NopaDajh8=Fene",
+            generate(
+                "⛄",
+                "banana colored duckling",
+                "masterpasswordapp.com",
+                1,
+                &Template::Long
+            )
+        );
+        assert_eq!(
+            "QesuHirv5-Xepl",
+            generate(
+                "Robert Lee Mitchell",
+                "⛄",
+                "masterpasswordapp.com",
+                1,
+                &Template::Long
+            )
+        );
+    }
+
+    #[test]
+    fn matches_reference_pins() {
+        let key = MasterKey::new("John Doe", "password");
+        for (site, counter, expected) in [
+            ("tomato", 1, "5914"),
+            ("potato", 1, "7329"),
+            ("carrot", 2, "2750"),
+        ] {
+            assert_eq!(expected, key.site_password(site, counter, &Template::Pin));
+        }
+    }
+}